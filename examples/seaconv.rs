@@ -1,14 +1,9 @@
-use bytemuck::cast_slice;
 use clap::{Arg, ArgAction, ArgMatches, Command};
 use sea_codec::{
-    decoder::SeaDecoder,
-    encoder::{EncoderSettings, SeaEncoder},
+    encoder::EncoderSettings,
+    wav::{SeaWavDecoder, SeaWavEncoder},
 };
-use std::{io::Cursor, path::Path};
-use wav::{read_wav, write_wav};
-
-#[path = "../tests/wav.rs"]
-mod wav;
+use std::path::Path;
 
 fn get_encoder_settings(matches: &ArgMatches) -> EncoderSettings {
     let frames_per_chunk = matches
@@ -153,31 +148,23 @@ fn main() {
 
     match (input_ext, output_ext) {
         (Some("wav"), Some("sea")) => {
-            let input_wave = read_wav(&Path::new(input)).unwrap_or_else(|_| {
-                eprintln!("Error: Failed to decode .wav file");
+            let input_file = std::fs::File::open(input).unwrap_or_else(|_| {
+                eprintln!("Error: Failed to open input file");
                 std::process::exit(1);
             });
 
-            let mut output_file = std::fs::File::create(output).unwrap_or_else(|_| {
+            let output_file = std::fs::File::create(output).unwrap_or_else(|_| {
                 eprintln!("Error: Failed to create output file");
                 std::process::exit(1);
             });
 
-            let u8_input_samples: &[u8] = cast_slice(&input_wave.samples);
-            let mut cursor: Cursor<_> = Cursor::new(u8_input_samples);
-
-            let mut sea_encoder = SeaEncoder::new(
-                input_wave.channels as u8,
-                input_wave.sample_rate,
-                Some(input_wave.samples.len() as u32 / input_wave.channels),
-                settings,
-                &mut cursor,
-                &mut output_file,
-            )
-            .unwrap_or_else(|_| {
-                eprintln!("Error: Failed to create encoder");
-                std::process::exit(1);
-            });
+            // `SeaWavEncoder` reads the `fmt ` chunk's format tag/bit depth itself and routes the
+            // encoder's ingest accordingly, instead of this binary downconverting to i16 by hand
+            let mut sea_encoder = SeaWavEncoder::new(input_file, output_file, settings)
+                .unwrap_or_else(|_| {
+                    eprintln!("Error: Failed to create encoder");
+                    std::process::exit(1);
+                });
 
             while sea_encoder.encode_frame().unwrap_or_else(|_| {
                 eprintln!("Error: Failed to encode frame");
@@ -190,13 +177,21 @@ fn main() {
             });
         }
         (Some("sea"), Some("wav")) => {
-            let mut input_file = std::fs::File::open(input).unwrap_or_else(|_| {
+            let input_file = std::fs::File::open(input).unwrap_or_else(|_| {
                 eprintln!("Error: Failed to open input file");
                 std::process::exit(1);
             });
 
-            let mut sea_decoded = Vec::<u8>::with_capacity(64 * 1024 * 1024);
-            let mut sea_decoder = SeaDecoder::new(&mut input_file, &mut sea_decoded).unwrap();
+            let output_file = std::fs::File::create(output).unwrap_or_else(|_| {
+                eprintln!("Error: Failed to create output file");
+                std::process::exit(1);
+            });
+
+            let mut sea_decoder =
+                SeaWavDecoder::new(input_file, output_file).unwrap_or_else(|_| {
+                    eprintln!("Error: Failed to create decoder");
+                    std::process::exit(1);
+                });
 
             while sea_decoder.decode_frame().unwrap_or_else(|_| {
                 eprintln!("Error: Failed to decode frame");
@@ -207,16 +202,6 @@ fn main() {
                 eprintln!("Error: Failed to finalize decoder");
                 std::process::exit(1);
             });
-
-            let info = sea_decoder.get_header();
-            let i16_decoded: &[i16] = cast_slice(&sea_decoded);
-
-            write_wav(i16_decoded, info.channels as u16, info.sample_rate, output).unwrap_or_else(
-                |_| {
-                    eprintln!("Error: Failed to encode wav file");
-                    std::process::exit(1);
-                },
-            );
         }
         _ => {
             eprintln!("Error: Invalid file extensions. Supported conversions are .wav to .sea and .sea to .wav");
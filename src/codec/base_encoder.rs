@@ -1,9 +1,70 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
 use super::{
     common::{clamp_i16, SeaResidualSize},
-    lms::{SeaLMS, LMS_LEN},
+    lms::SeaLMS,
+    lpc::SeaLpc,
     qt::SeaQuantTab,
 };
 
+/// Common interface `get_residuals_with_best_scalefactor` needs from a predictor: `SeaLMS`'s
+/// adaptive weights and `SeaLpc`'s fixed per-chunk coefficients both implement it, so the
+/// scalefactor/residual search below doesn't care which one it's driving.
+pub trait SeaPredictor: Clone {
+    fn predict(&self) -> i32;
+    fn update(&mut self, reconstructed: i16, dequantized: i32);
+    fn get_weights_penalty(&self) -> u64;
+
+    /// Rebuild this predictor ahead of encoding a new chunk, given that chunk's samples for
+    /// this predictor's channel (`channel_offset` into `samples`, interleaved every `channels`).
+    /// `SeaLMS`'s adaptive weights carry over between chunks, so the default is a no-op clone;
+    /// `SeaLpc` overrides this to re-run Levinson-Durbin, since its coefficients are fixed for
+    /// the chunk's duration and must be fit fresh each time.
+    fn refit_for_chunk(&self, samples: &[i16], channels: usize, channel_offset: usize) -> Self {
+        let _ = (samples, channels, channel_offset);
+        self.clone()
+    }
+}
+
+impl SeaPredictor for SeaLMS {
+    fn predict(&self) -> i32 {
+        SeaLMS::predict(self)
+    }
+
+    fn update(&mut self, reconstructed: i16, dequantized: i32) {
+        SeaLMS::update(self, reconstructed, dequantized)
+    }
+
+    fn get_weights_penalty(&self) -> u64 {
+        SeaLMS::get_weights_penalty(self)
+    }
+}
+
+impl SeaPredictor for SeaLpc {
+    fn predict(&self) -> i32 {
+        SeaLpc::predict(self)
+    }
+
+    fn update(&mut self, reconstructed: i16, dequantized: i32) {
+        SeaLpc::update(self, reconstructed, dequantized)
+    }
+
+    fn get_weights_penalty(&self) -> u64 {
+        SeaLpc::get_weights_penalty(self)
+    }
+
+    fn refit_for_chunk(&self, samples: &[i16], channels: usize, channel_offset: usize) -> Self {
+        let channel_samples: Vec<i32> = samples[channel_offset..]
+            .iter()
+            .step_by(channels)
+            .map(|&s| s as i32)
+            .collect();
+
+        SeaLpc::from_samples(self.order(), &channel_samples)
+    }
+}
+
 pub struct BaseEncoder {
     current_residuals: Vec<u8>,
 }
@@ -21,14 +82,14 @@ impl BaseEncoder {
         }
     }
 
-    fn calculate_residuals(
+    fn calculate_residuals<P: SeaPredictor>(
         &mut self,
         channels: usize,
         dequant_tab: &[i32],
         quant_tab: &SeaQuantTab,
         samples: &[i16],
         scalefactor: i32,
-        lms: &mut SeaLMS,
+        predictor: &mut P,
         best_rank: u64, // provided as optimization, can be u64::MAX if omitted
         residual_size: SeaResidualSize,
         scalefactor_reciprocals: &[i32],
@@ -41,7 +102,7 @@ impl BaseEncoder {
 
         for (index, sample_i16) in samples.iter().step_by(channels as usize).enumerate() {
             let sample = *sample_i16 as i32;
-            let predicted = lms.predict();
+            let predicted = predictor.predict();
             let residual = sample - predicted;
             let scaled = sea_div(
                 residual,
@@ -57,19 +118,19 @@ impl BaseEncoder {
 
             let error_sq = error.pow(2) as u64;
 
-            current_rank += error_sq + lms.get_weights_penalty();
+            current_rank += error_sq + predictor.get_weights_penalty();
             if current_rank > best_rank {
                 break;
             }
 
-            lms.update(reconstructed, dequantized);
+            predictor.update(reconstructed, dequantized);
             self.current_residuals[index] = quantized;
         }
 
         current_rank
     }
 
-    pub fn get_residuals_with_best_scalefactor(
+    pub fn get_residuals_with_best_scalefactor<P: SeaPredictor>(
         &mut self,
         channels: usize,
         quant_tab: &SeaQuantTab,
@@ -77,26 +138,27 @@ impl BaseEncoder {
         scalefactor_reciprocals: &[i32],
         samples: &[i16],
         prev_scalefactor: i32, // provided as optimization, can be 0
-        ref_lms: &SeaLMS,
+        ref_predictor: &P,
         residual_size: SeaResidualSize,
         scale_factor_bits: u8,
         best_residual_bits: &mut [u8],
-    ) -> (u64, SeaLMS, i32) {
+    ) -> (u64, P, i32) {
         let mut best_rank: u64 = u64::MAX;
 
         self.current_residuals.resize(best_residual_bits.len(), 0);
 
-        let mut best_lms = SeaLMS::new();
+        // overwritten on the first (always-improving) iteration below
+        let mut best_predictor = ref_predictor.clone();
         let mut best_scalefactor: i32 = 0;
 
-        let mut current_lms: SeaLMS = ref_lms.clone();
+        let mut current_predictor: P = ref_predictor.clone();
 
         let scalefactor_end = 1 << scale_factor_bits;
 
         for sfi in 0..scalefactor_end {
             let scalefactor: i32 = (sfi + prev_scalefactor) % scalefactor_end;
 
-            current_lms.clone_from(&ref_lms);
+            current_predictor.clone_from(ref_predictor);
 
             let dqt = &dequant_tab[scalefactor as usize];
 
@@ -106,7 +168,7 @@ impl BaseEncoder {
                 quant_tab,
                 &samples,
                 scalefactor,
-                &mut current_lms,
+                &mut current_predictor,
                 best_rank,
                 residual_size,
                 &scalefactor_reciprocals,
@@ -116,11 +178,11 @@ impl BaseEncoder {
                 best_rank = current_rank;
                 best_residual_bits[..self.current_residuals.len()]
                     .clone_from_slice(&self.current_residuals);
-                best_lms.clone_from(&current_lms);
+                best_predictor.clone_from(&current_predictor);
                 best_scalefactor = scalefactor;
             }
         }
 
-        (best_rank, best_lms, best_scalefactor)
+        (best_rank, best_predictor, best_scalefactor)
     }
 }
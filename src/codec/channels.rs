@@ -0,0 +1,106 @@
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
+use super::common::clamp_i16;
+
+/// Downmix/upmix coefficients between two fixed channel counts, applied to interleaved PCM
+/// ahead of encoding or after decoding - mirroring the channel ops nihav's `soundcvt` provides
+/// (passthrough, channel reorder, mono duplication, N->M remix matrices). `coefficients` is
+/// `output_channels` rows of `input_channels` columns; row `o` gives the weight each input
+/// channel contributes to output channel `o`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelMixer {
+    input_channels: usize,
+    output_channels: usize,
+    coefficients: Vec<f32>,
+}
+
+impl ChannelMixer {
+    /// Duplicates a single input channel across `output_channels` outputs, e.g. playing a mono
+    /// source back over several speakers.
+    pub fn duplicate(output_channels: usize) -> Self {
+        Self {
+            input_channels: 1,
+            output_channels,
+            coefficients: vec![1.0; output_channels],
+        }
+    }
+
+    /// Averages stereo down to mono: `0.5L + 0.5R`.
+    pub fn stereo_to_mono() -> Self {
+        Self {
+            input_channels: 2,
+            output_channels: 1,
+            coefficients: vec![0.5, 0.5],
+        }
+    }
+
+    /// Downmixes ITU 5.1 (L, R, C, LFE, Ls, Rs) to stereo, with the center channel folded in at
+    /// `1/sqrt(2)` and the surrounds at the usual ~0.7 downmix coefficient; the LFE channel is
+    /// dropped, matching common consumer downmix practice.
+    pub fn surround_5_1_to_stereo() -> Self {
+        const CENTER: f32 = core::f32::consts::FRAC_1_SQRT_2;
+        const SURROUND: f32 = 0.707;
+
+        #[rustfmt::skip]
+        let coefficients = vec![
+            1.0, 0.0, CENTER, 0.0, SURROUND, 0.0,
+            0.0, 1.0, CENTER, 0.0, 0.0, SURROUND,
+        ];
+
+        Self {
+            input_channels: 6,
+            output_channels: 2,
+            coefficients,
+        }
+    }
+
+    /// Picks out and/or reorders input channels with no mixing, e.g. swapping left/right or
+    /// dropping a channel an output layout doesn't need. `channel_map[o]` is the input channel
+    /// that becomes output channel `o`.
+    pub fn reorder(input_channels: usize, channel_map: &[usize]) -> Self {
+        let mut coefficients = vec![0.0; channel_map.len() * input_channels];
+        for (output_index, &source_channel) in channel_map.iter().enumerate() {
+            coefficients[output_index * input_channels + source_channel] = 1.0;
+        }
+
+        Self {
+            input_channels,
+            output_channels: channel_map.len(),
+            coefficients,
+        }
+    }
+
+    pub fn input_channels(&self) -> usize {
+        self.input_channels
+    }
+
+    pub fn output_channels(&self) -> usize {
+        self.output_channels
+    }
+
+    /// Remixes one block of interleaved `i16` samples from `input_channels` to `output_channels`.
+    pub fn process(&self, input: &[i16]) -> Vec<i16> {
+        assert_eq!(input.len() % self.input_channels, 0);
+
+        let frames = input.len() / self.input_channels;
+        let mut output = Vec::with_capacity(frames * self.output_channels);
+
+        for frame in input.chunks_exact(self.input_channels) {
+            for output_channel in 0..self.output_channels {
+                let row = &self.coefficients[output_channel * self.input_channels
+                    ..(output_channel + 1) * self.input_channels];
+
+                let mixed: f32 = frame
+                    .iter()
+                    .zip(row.iter())
+                    .map(|(&sample, &coefficient)| sample as f32 * coefficient)
+                    .sum();
+
+                output.push(clamp_i16(mixed.round() as i32));
+            }
+        }
+
+        output
+    }
+}
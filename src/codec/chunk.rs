@@ -1,34 +1,252 @@
-use std::usize;
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
 
 use crate::codec::{bits::BitUnpacker, common::clamp_i16, lms::LMS_LEN};
 
 use super::{
     bits::BitPacker,
-    common::{SeaDequantTab, SeaError, SeaResidualSize},
-    encoder::EncoderSettings,
+    common::{crc16, crc8, SeaDequantTab, SeaError, SeaResidualSize},
+    encoder::{EncoderSettings, SeaPredictorKind},
     file::SeaFileHeader,
     lms::SeaLMS,
+    lpc::{SeaLpc, SEA_LPC_MAX_ORDER},
 };
 
 #[derive(Debug, Clone, Copy)]
 pub enum SeaChunkType {
     CBR = 0x01,
     VBR = 0x02,
+    RICE = 0x03,
+    LPC = 0x04,
+}
+
+/// The per-sample predictor a decoded chunk was built with: either `SeaLMS`'s adaptive weights
+/// or `SeaLpc`'s fixed per-chunk coefficients. Chunk (de)serialization picks which one based on
+/// `SeaChunkType`; `predict`/`update` just forward to whichever variant is active.
+#[derive(Debug, Clone)]
+enum ChunkPredictor {
+    Lms(SeaLMS),
+    Lpc(SeaLpc),
+}
+
+impl ChunkPredictor {
+    fn predict(&self) -> i32 {
+        match self {
+            ChunkPredictor::Lms(lms) => lms.predict(),
+            ChunkPredictor::Lpc(lpc) => lpc.predict(),
+        }
+    }
+
+    fn update(&mut self, reconstructed: i16, dequantized: i32) {
+        match self {
+            ChunkPredictor::Lms(lms) => lms.update(reconstructed, dequantized),
+            ChunkPredictor::Lpc(lpc) => lpc.update(reconstructed, dequantized),
+        }
+    }
+}
+
+/// Per-chunk channel layout for 2-channel streams. `MidSide` stores `mid = (l + r) >> 1` and
+/// `side = l - r` in place of left/right, which `SeaChunk::decode` reverses after LMS
+/// reconstruction; see `encoder_cbr::mid_side_transform` for the forward half.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SeaStereoMode {
+    LeftRight = 0x00,
+    MidSide = 0x01,
+}
+
+impl SeaStereoMode {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0x01 => SeaStereoMode::MidSide,
+            _ => SeaStereoMode::LeftRight,
+        }
+    }
+}
+
+/// Minimal MSB-first bit reader used to decode the Golomb-Rice unary/remainder stream, which
+/// unlike the CBR/VBR residual streams has no fixed per-symbol width `BitUnpacker` can be
+/// preconfigured with.
+struct RiceBitReader<'a> {
+    bytes: &'a [u8],
+    byte_index: usize,
+    bit_index: u8,
+}
+
+impl<'a> RiceBitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            byte_index: 0,
+            bit_index: 0,
+        }
+    }
+
+    fn read_bit(&mut self) -> u32 {
+        let bit = (self.bytes[self.byte_index] >> (7 - self.bit_index)) & 1;
+        self.bit_index += 1;
+        if self.bit_index == 8 {
+            self.bit_index = 0;
+            self.byte_index += 1;
+        }
+        bit as u32
+    }
+
+    fn read_unary(&mut self) -> u32 {
+        let mut quotient = 0;
+        while self.read_bit() == 0 {
+            quotient += 1;
+        }
+        quotient
+    }
+
+    fn read_bits(&mut self, bits: u8) -> u32 {
+        let mut value = 0;
+        for _ in 0..bits {
+            value = (value << 1) | self.read_bit();
+        }
+        value
+    }
+}
+
+// upper bound on the partition order `choose_rice_partition_order` will try, i.e. at most
+// 2^MAX_RICE_PARTITION_ORDER partitions per channel per chunk
+const MAX_RICE_PARTITION_ORDER: u8 = 6;
+
+/// Picks the Rice parameter minimizing the coded size of `values` (already non-negative,
+/// zig-zag-style quantized residual indices): `k = floor(log2(mean))` is a good starting
+/// estimate, refined by checking its immediate neighbours.
+fn choose_rice_k(values: &[u8]) -> u8 {
+    if values.is_empty() {
+        return 0;
+    }
+
+    let cost = |k: i32| -> u64 {
+        values
+            .iter()
+            .map(|&v| ((v as u64) >> k) + 1 + k as u64)
+            .sum()
+    };
+
+    let mean = values.iter().map(|&v| v as f64).sum::<f64>() / values.len() as f64;
+    let estimate = if mean >= 1.0 { mean.log2().floor() as i32 } else { 0 };
+
+    let mut best_k = estimate.max(0);
+    let mut best_cost = cost(best_k);
+
+    for k in [estimate - 1, estimate + 1] {
+        if k < 0 {
+            continue;
+        }
+        let k_cost = cost(k);
+        if k_cost < best_cost {
+            best_cost = k_cost;
+            best_k = k;
+        }
+    }
+
+    best_k as u8
+}
+
+/// Splits each channel's residual stream into `2^p` partitions of `ceil(frames / 2^p)` frames (the
+/// last non-empty partition holds whatever is left over and, for some orders, trailing partitions
+/// are empty - the same rounding as the scale-factor grouping in `SeaChunk::from_slice`) and picks the partition
+/// order `p` minimizing total coded size, trying every order up to `MAX_RICE_PARTITION_ORDER`.
+/// Finer partitioning adapts `k` to local signal statistics at the cost of a `k` per partition
+/// instead of one per chunk; coarser partitioning pays less overhead but tracks changes in the
+/// residual distribution less closely. Returns the chosen order and the per-partition,
+/// per-channel `k` values (0 for an empty partition), ordered partition-major then channel-minor to match
+/// `serialize_rice_residuals`/`SeaChunk::from_slice`.
+fn choose_rice_partition_order(channel_residuals: &[Vec<u8>]) -> (u8, Vec<u8>) {
+    let frames = channel_residuals.first().map_or(0, |channel| channel.len());
+    if frames == 0 {
+        return (0, Vec::new());
+    }
+
+    let mut best_order = 0;
+    let mut best_cost = u64::MAX;
+    let mut best_params = Vec::new();
+
+    for order in 0..=MAX_RICE_PARTITION_ORDER {
+        let partitions = 1usize << order;
+        if partitions > frames {
+            break;
+        }
+        let partition_size = frames.div_ceil(partitions);
+
+        let mut params = Vec::with_capacity(partitions * channel_residuals.len());
+        let mut cost = 0u64;
+
+        for partition in 0..partitions {
+            let start = partition * partition_size;
+            let end = ((partition + 1) * partition_size).min(frames);
+            if start >= end {
+                // still stored, so the `k` list is `2^order * channels` long as `from_slice` reads it
+                params.extend(core::iter::repeat(0).take(channel_residuals.len()));
+                cost += 4 * channel_residuals.len() as u64;
+                continue;
+            }
+
+            for channel in channel_residuals {
+                let block = &channel[start..end];
+                let k = choose_rice_k(block);
+                cost += 4; // the k parameter itself, stored 4 bits wide
+                cost += block
+                    .iter()
+                    .map(|&v| ((v as u64) >> k) + 1 + k as u64)
+                    .sum::<u64>();
+                params.push(k);
+            }
+        }
+
+        if cost < best_cost {
+            best_cost = cost;
+            best_order = order;
+            best_params = params;
+        }
+    }
+
+    (best_order, best_params)
+}
+
+/// Checks a serialized chunk's CRC-8 header and CRC-16 footer without parsing the rest of it into
+/// a `SeaChunk`. `SeaChunk::from_slice`'s own `verify_crc` path uses this; it's also `pub` so a
+/// conformance test harness can assert a chunk's checksum is stable across versions without
+/// paying for a full decode first.
+pub fn verify_checksums(encoded: &[u8]) -> bool {
+    if encoded.len() < 8 {
+        return false;
+    }
+
+    let body_end = encoded.len() - 2;
+
+    if encoded[5] != crc8(&encoded[6..body_end]) {
+        return false;
+    }
+
+    let footer = u16::from_be_bytes(encoded[body_end..].try_into().unwrap());
+    footer == crc16(&encoded[..body_end])
 }
 
 #[derive(Debug)]
 pub struct SeaChunk {
     file_header: SeaFileHeader,
     chunk_type: SeaChunkType,
+    stereo_mode: SeaStereoMode,
+    // order of each `lpc` entry; 0 unless `chunk_type` is `LPC`
+    lpc_order: u8,
 
     pub scale_factor_bits: u8,
     pub scale_factor_frames: u8,
     pub residual_size: SeaResidualSize,
 
     pub lms: Vec<SeaLMS>,
+    pub lpc: Vec<SeaLpc>,
 
     pub scale_factors: Vec<u8>,
     pub vbr_residual_sizes: Vec<u8>,
+    // `2^rice_partition_order` equal-size partitions per channel; 0 unless `chunk_type` is `RICE`
+    pub rice_partition_order: u8,
+    pub rice_params: Vec<u8>,
     pub residuals: Vec<u8>,
 }
 
@@ -36,35 +254,78 @@ impl SeaChunk {
     pub fn new(
         file_header: &SeaFileHeader,
         lms: &Vec<SeaLMS>,
+        lpc: &Vec<SeaLpc>,
         encoder_settings: &EncoderSettings,
         scale_factors: Vec<u8>,
         vbr_residual_sizes: Vec<u8>,
         residuals: Vec<u8>,
+        stereo_mode: u8,
     ) -> SeaChunk {
         let is_vbr = vbr_residual_sizes.len() > 0;
 
+        let chunk_type = if is_vbr {
+            SeaChunkType::VBR
+        } else if let SeaPredictorKind::Lpc { .. } = encoder_settings.predictor {
+            SeaChunkType::LPC
+        } else if encoder_settings.rice {
+            SeaChunkType::RICE
+        } else {
+            SeaChunkType::CBR
+        };
+
+        let scale_factor_frames = encoder_settings.scale_factor_frames;
+        let channels = file_header.channels as usize;
+
+        let (rice_partition_order, rice_params) = if matches!(chunk_type, SeaChunkType::RICE) {
+            let frames = residuals.len() / channels;
+            let channel_residuals: Vec<Vec<u8>> = (0..channels)
+                .map(|channel_index| {
+                    (0..frames)
+                        .map(|frame| residuals[frame * channels + channel_index])
+                        .collect()
+                })
+                .collect();
+            choose_rice_partition_order(&channel_residuals)
+        } else {
+            (0, Vec::new())
+        };
+
+        let lpc_order = if let SeaPredictorKind::Lpc { order } = encoder_settings.predictor {
+            order
+        } else {
+            0
+        };
+
         SeaChunk {
             file_header: file_header.clone(),
-            chunk_type: if is_vbr {
-                SeaChunkType::VBR
-            } else {
-                SeaChunkType::CBR
-            },
+            chunk_type,
+            stereo_mode: SeaStereoMode::from_u8(stereo_mode),
+            lpc_order,
             scale_factor_bits: encoder_settings.scale_factor_bits,
-            scale_factor_frames: encoder_settings.scale_factor_frames,
+            scale_factor_frames,
             residual_size: SeaResidualSize::from(encoder_settings.residual_bits.floor() as u8),
 
             lms: lms.clone(),
+            lpc: lpc.clone(),
             scale_factors,
             vbr_residual_sizes,
+            rice_partition_order,
+            rice_params,
             residuals,
         }
     }
 
+    /// `verify_crc` gates strict integrity checking: when `true`, the sixth header byte is
+    /// checked as a CRC-8/SMBUS of the chunk body and the trailing two bytes are checked as a
+    /// CRC-16/ARC footer over the whole chunk; either mismatching returns
+    /// `SeaError::ChecksumMismatch`. With `false` neither checksum is checked, but the chunk is
+    /// still parsed as this 6-byte-header, 2-byte-footer layout: chunks in the earlier 4-byte
+    /// header layout (a `0x5A` filler byte and no footer) are not readable in either mode.
     pub fn from_slice(
         encoded: &[u8],
         file_header: &SeaFileHeader,
         remaining_frames: Option<usize>,
+        verify_crc: bool,
     ) -> Result<Self, SeaError> {
         assert!(encoded.len() <= file_header.chunk_size as usize);
 
@@ -76,24 +337,75 @@ impl SeaChunk {
         let chunk_type: SeaChunkType = match encoded[0] {
             0x01 => SeaChunkType::CBR,
             0x02 => SeaChunkType::VBR,
+            0x03 => SeaChunkType::RICE,
+            0x04 => SeaChunkType::LPC,
             _ => return Err(SeaError::InvalidFile),
         };
 
         let scale_factor_bits = encoded[1] >> 4;
         let residual_size = SeaResidualSize::from(encoded[1] & 0b1111);
         let scale_factor_frames = encoded[2];
-        let _reserved = encoded[3];
+        let stereo_mode = SeaStereoMode::from_u8(encoded[3]);
+        // only meaningful when chunk_type is LPC; 0 otherwise. bounds-checked here since this
+        // byte comes straight from the file and otherwise reaches `SeaLpc::from_coefficients` ->
+        // `SeaLpc::new`'s `assert!(order > 0 && order <= SEA_LPC_MAX_ORDER)`, which would panic
+        // the decoder on a corrupted or malicious chunk instead of returning a `SeaError`
+        let lpc_order = if matches!(chunk_type, SeaChunkType::LPC) {
+            let order = encoded[4];
+            if order == 0 || order as usize > SEA_LPC_MAX_ORDER {
+                return Err(SeaError::InvalidFrame);
+            }
+            order
+        } else {
+            0
+        };
+        // only meaningful when chunk_type is RICE; 0 otherwise (see `serialize_header`). bounds-
+        // checked here since this byte comes straight from the file and is about to drive
+        // `1usize << rice_partition_order` plus allocations sized off it - unchecked, a corrupted
+        // value above the encoder's own `MAX_RICE_PARTITION_ORDER` overflow-shifts or allocates
+        // wildly instead of returning a `SeaError`
+        let rice_partition_order = if matches!(chunk_type, SeaChunkType::RICE) {
+            let order = encoded[4];
+            if order > MAX_RICE_PARTITION_ORDER {
+                return Err(SeaError::InvalidFrame);
+            }
+            order
+        } else {
+            0
+        };
+        // the last two bytes of every chunk are a CRC-16/ARC footer over everything before it,
+        // mirroring FLAC's frame-header-CRC-8 + frame-footer-CRC-16 pair
+        let body_end = encoded.len() - 2;
+
+        if verify_crc && !verify_checksums(encoded) {
+            return Err(SeaError::ChecksumMismatch);
+        }
 
-        let mut encoded_index = 4;
+        let mut encoded_index = 6;
 
         let mut lms: Vec<SeaLMS> = vec![];
-        for _ in 0..file_header.channels as usize {
-            lms.push(SeaLMS::from_bytes(
-                &encoded[encoded_index..encoded_index + LMS_LEN * 4]
-                    .try_into()
-                    .unwrap(),
-            ));
-            encoded_index += LMS_LEN * 4;
+        let mut lpc: Vec<SeaLpc> = vec![];
+
+        if matches!(chunk_type, SeaChunkType::LPC) {
+            for _ in 0..file_header.channels as usize {
+                let mut coefficients = [0i32; SEA_LPC_MAX_ORDER];
+                for coefficient in coefficients.iter_mut().take(lpc_order as usize) {
+                    *coefficient = i32::from_le_bytes(
+                        encoded[encoded_index..encoded_index + 4].try_into().unwrap(),
+                    );
+                    encoded_index += 4;
+                }
+                lpc.push(SeaLpc::from_coefficients(lpc_order as usize, coefficients));
+            }
+        } else {
+            for _ in 0..file_header.channels as usize {
+                lms.push(SeaLMS::from_bytes(
+                    &encoded[encoded_index..encoded_index + LMS_LEN * 4]
+                        .try_into()
+                        .unwrap(),
+                ));
+                encoded_index += LMS_LEN * 4;
+            }
         }
 
         let frames_in_this_chunk =
@@ -135,7 +447,51 @@ impl SeaChunk {
             Vec::new()
         };
 
-        let residuals: Vec<u8> = {
+        let rice_partitions = 1usize << rice_partition_order;
+
+        let rice_params: Vec<u8> = if matches!(chunk_type, SeaChunkType::RICE) {
+            let rice_param_items = rice_partitions * file_header.channels as usize;
+            let packed_rice_param_bytes = (rice_param_items * 4).div_ceil(8);
+            let packed_rice_params =
+                &encoded[encoded_index..encoded_index + packed_rice_param_bytes];
+            encoded_index += packed_rice_param_bytes;
+
+            let mut unpacker = BitUnpacker::new_const_bits(4);
+            unpacker.process_bytes(&packed_rice_params);
+            let mut res = unpacker.finish();
+            res.resize(rice_param_items, 0);
+            res
+        } else {
+            Vec::new()
+        };
+
+        let residuals: Vec<u8> = if matches!(chunk_type, SeaChunkType::RICE) {
+            let channels = file_header.channels as usize;
+            let partition_size = frames_in_this_chunk.div_ceil(rice_partitions);
+            let mut reader = RiceBitReader::new(&encoded[encoded_index..body_end]);
+            let mut residuals = vec![0u8; frames_in_this_chunk * channels];
+
+            for partition in 0..rice_partitions {
+                let frame_start = partition * partition_size;
+                let frame_end = ((partition + 1) * partition_size).min(frames_in_this_chunk);
+                if frame_start >= frame_end {
+                    continue;
+                }
+
+                for channel_index in 0..channels {
+                    let rice_k = rice_params[partition * channels + channel_index];
+
+                    for frame in frame_start..frame_end {
+                        let quotient = reader.read_unary();
+                        let remainder = reader.read_bits(rice_k);
+                        let value = (quotient << rice_k) | remainder;
+                        residuals[frame * channels + channel_index] = value as u8;
+                    }
+                }
+            }
+
+            residuals
+        } else {
             let mut unpacker = if matches!(chunk_type, SeaChunkType::VBR) {
                 let mut bitlengths = Vec::new();
                 for vbr_chunk in vbr_residual_sizes.chunks_exact(file_header.channels as usize) {
@@ -193,13 +549,18 @@ impl SeaChunk {
         Ok(Self {
             file_header: file_header.clone(),
             chunk_type,
+            stereo_mode,
+            lpc_order,
             scale_factor_bits,
             scale_factor_frames,
             residual_size,
 
             lms,
+            lpc,
             scale_factors,
             vbr_residual_sizes,
+            rice_partition_order,
+            rice_params,
             residuals,
         })
     }
@@ -209,7 +570,11 @@ impl SeaChunk {
             self.file_header.frames_per_chunk as usize * self.file_header.channels as usize,
         );
 
-        let mut lms = self.lms.clone();
+        let mut predictors: Vec<ChunkPredictor> = if matches!(self.chunk_type, SeaChunkType::LPC) {
+            self.lpc.iter().cloned().map(ChunkPredictor::Lpc).collect()
+        } else {
+            self.lms.iter().cloned().map(ChunkPredictor::Lms).collect()
+        };
 
         let dqts: Vec<Vec<Vec<i32>>> = (1..=8)
             .map(|i| {
@@ -236,7 +601,7 @@ impl SeaChunk {
 
                 let scale_factor = self.scale_factors[scale_factor_index + channel_index];
 
-                let predicted = lms[channel_index].predict();
+                let predicted = predictors[channel_index].predict();
 
                 let quantized: usize = *residual as usize;
 
@@ -245,25 +610,46 @@ impl SeaChunk {
 
                 let reconstructed = clamp_i16(predicted + dequantized);
                 output.push(reconstructed);
-                lms[channel_index].update(reconstructed as i16, dequantized);
+                predictors[channel_index].update(reconstructed as i16, dequantized);
+            }
+        }
+
+        if self.stereo_mode == SeaStereoMode::MidSide {
+            for pair in output.chunks_exact_mut(2) {
+                let mid = pair[0] as i32;
+                let side = pair[1] as i32;
+                let r = mid - (side >> 1);
+                let l = r + side;
+                pair[0] = clamp_i16(l);
+                pair[1] = clamp_i16(r);
             }
         }
 
         output
     }
 
-    fn serialize_header(&self) -> [u8; 4] {
+    fn serialize_header(&self, payload: &[u8]) -> [u8; 6] {
         assert!(self.scale_factor_bits > 0);
         assert!(self.scale_factor_frames > 0);
         assert!(
             self.file_header.frames_per_chunk as usize % self.scale_factor_frames as usize == 0
         );
 
+        // `LPC` and `RICE` chunks are mutually exclusive (see `SeaChunk::new`), so this byte
+        // doubles as `rice_partition_order` for `RICE` chunks rather than growing the header
+        let fifth_byte = if matches!(self.chunk_type, SeaChunkType::RICE) {
+            self.rice_partition_order
+        } else {
+            self.lpc_order
+        };
+
         [
             self.chunk_type as u8,
             (self.scale_factor_bits << 4) as u8 | self.residual_size as u8,
             self.scale_factor_frames,
-            0x5A,
+            self.stereo_mode as u8,
+            fifth_byte,
+            crc8(payload),
         ]
     }
 
@@ -276,6 +662,19 @@ impl SeaChunk {
             .collect::<Vec<_>>()
     }
 
+    fn serialize_lpc(&self) -> Vec<u8> {
+        assert_eq!(self.file_header.channels as usize, self.lpc.len());
+
+        self.lpc
+            .iter()
+            .flat_map(|lpc| {
+                lpc.coefficients()[..lpc.order()]
+                    .iter()
+                    .flat_map(|c| c.to_le_bytes())
+            })
+            .collect::<Vec<_>>()
+    }
+
     fn serialize_scale_factors(&self) -> Vec<u8> {
         let mut packer = BitPacker::new();
         for scale_factor in self.scale_factors.iter() {
@@ -293,7 +692,51 @@ impl SeaChunk {
         packer.finish()
     }
 
+    fn serialize_rice_params(&self) -> Vec<u8> {
+        let mut packer = BitPacker::new();
+        for rice_param in self.rice_params.iter() {
+            packer.push(*rice_param as u32, 4);
+        }
+        packer.finish()
+    }
+
+    fn serialize_rice_residuals(&self) -> Vec<u8> {
+        let channels = self.file_header.channels as usize;
+        let frames = self.residuals.len() / channels;
+        let partitions = 1usize << self.rice_partition_order;
+        let partition_size = frames.div_ceil(partitions);
+        let mut packer = BitPacker::new();
+
+        for partition in 0..partitions {
+            let frame_start = partition * partition_size;
+            let frame_end = ((partition + 1) * partition_size).min(frames);
+            if frame_start >= frame_end {
+                continue;
+            }
+
+            for channel_index in 0..channels {
+                let rice_k = self.rice_params[partition * channels + channel_index];
+
+                for frame in frame_start..frame_end {
+                    let value = self.residuals[frame * channels + channel_index] as u32;
+                    let quotient = value >> rice_k;
+                    for _ in 0..quotient {
+                        packer.push(0, 1);
+                    }
+                    packer.push(1, 1);
+                    packer.push(value, rice_k);
+                }
+            }
+        }
+
+        packer.finish()
+    }
+
     fn serialize_residuals(&self) -> Vec<u8> {
+        if matches!(self.chunk_type, SeaChunkType::RICE) {
+            return self.serialize_rice_residuals();
+        }
+
         let mut packer = BitPacker::new();
         if matches!(self.chunk_type, SeaChunkType::VBR) {
             let mut vbr_residual_index = 0;
@@ -324,15 +767,26 @@ impl SeaChunk {
     }
 
     pub fn serialize(&self) -> Vec<u8> {
-        let mut output = Vec::new();
+        let mut payload = Vec::new();
 
-        output.extend_from_slice(&self.serialize_header());
-        output.extend_from_slice(&self.serialize_lms());
-        output.extend_from_slice(&self.serialize_scale_factors());
+        if matches!(self.chunk_type, SeaChunkType::LPC) {
+            payload.extend_from_slice(&self.serialize_lpc());
+        } else {
+            payload.extend_from_slice(&self.serialize_lms());
+        }
+        payload.extend_from_slice(&self.serialize_scale_factors());
         if matches!(self.chunk_type, SeaChunkType::VBR) {
-            output.extend_from_slice(&self.serialize_vbr_residual_sizes());
+            payload.extend_from_slice(&self.serialize_vbr_residual_sizes());
         }
-        output.extend_from_slice(&self.serialize_residuals());
+        if matches!(self.chunk_type, SeaChunkType::RICE) {
+            payload.extend_from_slice(&self.serialize_rice_params());
+        }
+        payload.extend_from_slice(&self.serialize_residuals());
+
+        let mut output = Vec::with_capacity(payload.len() + 8);
+        output.extend_from_slice(&self.serialize_header(&payload));
+        output.extend_from_slice(&payload);
+        output.extend_from_slice(&crc16(&output).to_be_bytes());
 
         output
     }
@@ -1,6 +1,8 @@
-use std::io;
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
 
 use super::dqt::SeaDequantTab;
+use crate::io::Read;
 
 pub const SEAC_MAGIC: u32 = u32::from_be_bytes(*b"seac"); // 0x73 0x65 0x61 0x63
 pub const SEA_MAX_CHANNELS: u8 = 32;
@@ -10,6 +12,56 @@ pub fn clamp_i16(v: i32) -> i16 {
     v.clamp(i16::MIN as i32, i16::MAX as i32) as i16
 }
 
+/// Generalized form of `clamp_i16`, parameterized by a stored sample depth in `1..=32` bits, for
+/// clamping audio on its way back out to a wider container format (see `SeaSampleFormat::from_i16`).
+/// `clamp_i16` stays the clamp the codec's internal pipeline itself uses for its own i16 domain
+/// (LMS prediction, dequantization) - widening that pipeline to carry `bits` of precision
+/// end to end would need the LMS weights and dequant tables widened too, which this doesn't do.
+#[inline(always)]
+pub fn clamp_sample(v: i32, bits: u8) -> i32 {
+    let max = (1i64 << (bits - 1)) - 1;
+    let min = -(1i64 << (bits - 1));
+    v.clamp(min as i32, max as i32)
+}
+
+// CRC-8/SMBUS (poly 0x07, init 0x00, no reflection, no final xor), same convention FLAC uses
+// for its frame header check
+pub fn crc8(data: &[u8]) -> u8 {
+    let mut crc: u8 = 0x00;
+
+    for &byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+    }
+
+    crc
+}
+
+// CRC-16/ARC (poly 0x8005, init 0x0000, reflected in/out, no final xor), same convention
+// flacenc uses for its frame footer check
+pub fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0x0000;
+
+    for &byte in data {
+        crc ^= byte as u16;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xA001
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    crc
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum SeaResidualSize {
     One = 1,
@@ -59,51 +111,56 @@ pub enum SeaError {
     InvalidParameters,
     InvalidFile,
     InvalidFrame,
+    // the CRC-8 chunk header or CRC-16 chunk footer didn't match the chunk's actual bytes;
+    // only returned when verify mode is enabled, see `SeaDecoder::set_verify`
+    ChecksumMismatch,
     EncoderClosed,
     UnsupportedVersion,
     TooManyFrames,
     MetadataTooLarge,
-    IoError(io::Error),
+    #[cfg(feature = "std")]
+    IoError(std::io::Error),
 }
 
-impl From<io::Error> for SeaError {
-    fn from(error: io::Error) -> Self {
+#[cfg(feature = "std")]
+impl From<std::io::Error> for SeaError {
+    fn from(error: std::io::Error) -> Self {
         SeaError::IoError(error)
     }
 }
 
 #[inline(always)]
-pub fn read_bytes<R: io::Read, const BYTES: usize>(mut reader: R) -> io::Result<[u8; BYTES]> {
+pub fn read_bytes<R: Read, const BYTES: usize>(mut reader: R) -> Result<[u8; BYTES], SeaError> {
     let mut buf = [0_u8; BYTES];
     reader.read_exact(&mut buf)?;
     Ok(buf)
 }
 
 #[inline(always)]
-pub fn read_u8<R: io::Read>(reader: R) -> io::Result<u8> {
+pub fn read_u8<R: Read>(reader: R) -> Result<u8, SeaError> {
     let data: [u8; 1] = read_bytes(reader)?;
     Ok(data[0])
 }
 
 #[inline(always)]
-pub fn read_u16_le<R: io::Read>(reader: R) -> io::Result<u16> {
+pub fn read_u16_le<R: Read>(reader: R) -> Result<u16, SeaError> {
     let data = read_bytes(reader)?;
     Ok(u16::from_le_bytes(data))
 }
 
 #[inline(always)]
-pub fn read_u32_be<R: io::Read>(reader: R) -> io::Result<u32> {
+pub fn read_u32_be<R: Read>(reader: R) -> Result<u32, SeaError> {
     let data = read_bytes(reader)?;
     Ok(u32::from_be_bytes(data))
 }
 
 #[inline(always)]
-pub fn read_u32_le<R: io::Read>(reader: R) -> io::Result<u32> {
+pub fn read_u32_le<R: Read>(reader: R) -> Result<u32, SeaError> {
     let data = read_bytes(reader)?;
     Ok(u32::from_le_bytes(data))
 }
 
-pub fn read_max_or_zero<R: io::Read>(mut reader: R, at_least_bytes: usize) -> io::Result<Vec<u8>> {
+pub fn read_max_or_zero<R: Read>(mut reader: R, at_least_bytes: usize) -> Result<Vec<u8>, SeaError> {
     let mut buffer = vec![0u8; at_least_bytes];
     let mut total_bytes_read = 0;
 
@@ -130,6 +187,9 @@ pub struct EncodedSamples {
     pub scale_factors: Vec<u8>,
     pub residuals: Vec<u8>,
     pub residual_bits: Vec<u8>,
+    // see `chunk::SeaStereoMode`; 0 (left/right, untransformed) unless the encoder tried joint
+    // stereo and a transformed mode won
+    pub stereo_mode: u8,
 }
 
 pub trait SeaEncoderTrait {
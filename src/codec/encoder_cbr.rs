@@ -1,10 +1,15 @@
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
 use super::{
-    base_encoder::BaseEncoder,
-    common::{EncodedSamples, SeaEncoderTrait, SeaResidualSize, SEA_MAX_CHANNELS},
+    base_encoder::{BaseEncoder, SeaPredictor},
+    chunk::SeaStereoMode,
+    common::{clamp_i16, EncodedSamples, SeaEncoderTrait, SeaResidualSize, SEA_MAX_CHANNELS},
     dqt::SeaDequantTab,
-    encoder::EncoderSettings,
+    encoder::{EncoderSettings, SeaPredictorKind},
     file::SeaFileHeader,
     lms::SeaLMS,
+    lpc::SeaLpc,
     qt::SeaQuantTab,
 };
 
@@ -13,79 +18,231 @@ pub struct CbrEncoder {
     residual_size: SeaResidualSize,
     scale_factor_frames: u8,
     scale_factor_bits: u8,
+    joint_stereo: bool,
+    predictor_kind: SeaPredictorKind,
     prev_scalefactor: [i32; SEA_MAX_CHANNELS as usize],
     base_encoder: BaseEncoder,
     pub lms: Vec<SeaLMS>,
+    pub lpc: Vec<SeaLpc>,
+}
+
+// reversible lifting transform (the same identity JPEG2000's RCT and FLAC's mid/side use):
+// mid = floor((l + r) / 2), side = l - r, and the inverse recovers l/r with no rounding loss.
+// `side` needs a 17th bit for extreme pairs (e.g. l = i16::MAX, r = i16::MIN); since nothing
+// downstream of this carries more than 16 bits per sample, we can't store that 17th bit anywhere,
+// so rather than clamp it (and so corrupt the l/r reconstruction - silently lossy in a codec whose
+// whole premise is exact, CRC-verified chunks) we refuse the transform for the whole chunk and let
+// `run_stereo_trial` fall back to untransformed left/right.
+pub(crate) fn mid_side_transform(samples: &[i16]) -> Option<Vec<i16>> {
+    let mut output = Vec::with_capacity(samples.len());
+
+    for pair in samples.chunks_exact(2) {
+        let l = pair[0] as i32;
+        let r = pair[1] as i32;
+        let side = l - r;
+
+        if side < i16::MIN as i32 || side > i16::MAX as i32 {
+            return None;
+        }
+
+        output.push(clamp_i16((l + r) >> 1));
+        output.push(side as i16);
+    }
+
+    Some(output)
 }
 
 impl CbrEncoder {
     pub fn new(file_header: &SeaFileHeader, encoder_settings: &EncoderSettings) -> Self {
+        let lpc = match encoder_settings.predictor {
+            SeaPredictorKind::Lpc { order } => (0..file_header.channels)
+                .map(|_| SeaLpc::new(order as usize))
+                .collect(),
+            SeaPredictorKind::Lms => Vec::new(),
+        };
+
         CbrEncoder {
             file_header: file_header.clone(),
             residual_size: SeaResidualSize::from(encoder_settings.residual_bits.floor() as u8),
             scale_factor_frames: encoder_settings.scale_factor_frames,
             scale_factor_bits: encoder_settings.scale_factor_bits,
+            joint_stereo: encoder_settings.joint_stereo,
+            predictor_kind: encoder_settings.predictor,
             prev_scalefactor: [0; SEA_MAX_CHANNELS as usize],
             base_encoder: BaseEncoder::new(),
             lms: SeaLMS::init_vec(file_header.channels as u32),
+            lpc,
         }
     }
-}
 
-impl SeaEncoderTrait for CbrEncoder {
-    fn encode(
-        &mut self,
+    /// Runs the per-slice scale-factor/residual search over `samples` starting from
+    /// `predictors`, and returns the encoded data alongside the total rank so callers can
+    /// compare candidate channel layouts (e.g. left/right vs. mid/side) without committing to
+    /// either yet. `predictors` is refit for this chunk's samples before the search starts, so
+    /// `SeaLpc`'s per-chunk coefficients see the right channel layout's samples.
+    fn encode_channels<P: SeaPredictor>(
+        &self,
         samples: &[i16],
         quant_tab: &SeaQuantTab,
-        dequant_tab: &mut SeaDequantTab,
-    ) -> EncodedSamples {
+        dqt: &Vec<Vec<i32>>,
+        scalefactor_reciprocals: &[i32],
+        predictors: &[P],
+        prev_scalefactor: &[i32; SEA_MAX_CHANNELS as usize],
+    ) -> (Vec<u8>, Vec<u8>, u64, Vec<P>, [i32; SEA_MAX_CHANNELS as usize]) {
+        let channels = self.file_header.channels as usize;
         let mut scale_factors = Vec::<u8>::new();
         let mut residuals = vec![0u8; samples.len()];
+        let mut total_rank: u64 = 0;
 
-        let dqt: &Vec<Vec<i32>> = dequant_tab.get_dqt(self.residual_size as usize);
-
-        let slice_size = self.scale_factor_frames as usize * self.file_header.channels as usize;
-
-        let scalefactor_reciprocals =
-            dequant_tab.get_scalefactor_reciprocals(self.residual_size as usize);
+        let slice_size = self.scale_factor_frames as usize * channels;
+        let best_residual_bits: &mut [u8] = &mut vec![0u8; slice_size / channels];
 
-        let best_residual_bits: &mut [u8] =
-            &mut vec![0u8; slice_size / self.file_header.channels as usize];
+        let mut predictors: Vec<P> = predictors
+            .iter()
+            .enumerate()
+            .map(|(channel_offset, predictor)| {
+                predictor.refit_for_chunk(samples, channels, channel_offset)
+            })
+            .collect();
+        let mut prev_scalefactor = *prev_scalefactor;
+        let mut base_encoder = BaseEncoder::new();
 
         for (slice_index, input_slice) in samples.chunks(slice_size).enumerate() {
-            for channel_offset in 0..self.file_header.channels as usize {
-                let (_best_rank, best_lms, best_scalefactor) =
-                    self.base_encoder.get_residuals_with_best_scalefactor(
-                        self.file_header.channels as usize,
+            for channel_offset in 0..channels {
+                let (best_rank, best_predictor, best_scalefactor) = base_encoder
+                    .get_residuals_with_best_scalefactor(
+                        channels,
                         quant_tab,
                         dqt,
                         scalefactor_reciprocals,
                         &input_slice[channel_offset..],
-                        self.prev_scalefactor[channel_offset] as i32,
-                        &self.lms[channel_offset],
+                        prev_scalefactor[channel_offset],
+                        &predictors[channel_offset],
                         self.residual_size,
                         self.scale_factor_bits,
                         best_residual_bits,
                     );
 
-                self.prev_scalefactor[channel_offset] = best_scalefactor;
-                self.lms[channel_offset] = best_lms;
+                prev_scalefactor[channel_offset] = best_scalefactor;
+                predictors[channel_offset] = best_predictor;
+                total_rank += best_rank;
 
                 scale_factors.push(best_scalefactor as u8);
 
                 // residuals need to be interleaved
                 for i in 0..best_residual_bits.len() {
-                    residuals[slice_index * slice_size
-                        + i * self.file_header.channels as usize
-                        + channel_offset] = best_residual_bits[i];
+                    residuals[slice_index * slice_size + i * channels + channel_offset] =
+                        best_residual_bits[i];
+                }
+            }
+        }
+
+        (scale_factors, residuals, total_rank, predictors, prev_scalefactor)
+    }
+
+    /// Tries left/right (and, when joint stereo is enabled on a 2-channel stream, mid/side) and
+    /// keeps whichever ranks lower, generic over which predictor is driving the search.
+    fn run_stereo_trial<P: SeaPredictor>(
+        &self,
+        samples: &[i16],
+        quant_tab: &SeaQuantTab,
+        dqt: &Vec<Vec<i32>>,
+        scalefactor_reciprocals: &[i32],
+        predictors: &[P],
+        prev_scalefactor: &[i32; SEA_MAX_CHANNELS as usize],
+    ) -> (Vec<u8>, Vec<u8>, SeaStereoMode, Vec<P>, [i32; SEA_MAX_CHANNELS as usize]) {
+        let left_right = self.encode_channels(
+            samples,
+            quant_tab,
+            dqt,
+            scalefactor_reciprocals,
+            predictors,
+            prev_scalefactor,
+        );
+
+        if self.joint_stereo && self.file_header.channels == 2 {
+            let mid_side = mid_side_transform(samples).map(|mid_side_samples| {
+                self.encode_channels(
+                    &mid_side_samples,
+                    quant_tab,
+                    dqt,
+                    scalefactor_reciprocals,
+                    predictors,
+                    prev_scalefactor,
+                )
+            });
+
+            match mid_side {
+                Some(mid_side) if mid_side.2 < left_right.2 => {
+                    (mid_side.0, mid_side.1, SeaStereoMode::MidSide, mid_side.3, mid_side.4)
                 }
+                _ => (
+                    left_right.0,
+                    left_right.1,
+                    SeaStereoMode::LeftRight,
+                    left_right.3,
+                    left_right.4,
+                ),
             }
+        } else {
+            (
+                left_right.0,
+                left_right.1,
+                SeaStereoMode::LeftRight,
+                left_right.3,
+                left_right.4,
+            )
         }
+    }
+}
+
+impl SeaEncoderTrait for CbrEncoder {
+    fn encode(
+        &mut self,
+        samples: &[i16],
+        quant_tab: &SeaQuantTab,
+        dequant_tab: &mut SeaDequantTab,
+    ) -> EncodedSamples {
+        let dqt: &Vec<Vec<i32>> = dequant_tab.get_dqt(self.residual_size as usize);
+        let scalefactor_reciprocals =
+            dequant_tab.get_scalefactor_reciprocals(self.residual_size as usize);
+
+        let (scale_factors, residuals, stereo_mode, prev_scalefactor) = match self.predictor_kind {
+            SeaPredictorKind::Lms => {
+                let (scale_factors, residuals, stereo_mode, lms, prev_scalefactor) = self
+                    .run_stereo_trial(
+                        samples,
+                        quant_tab,
+                        dqt,
+                        scalefactor_reciprocals,
+                        &self.lms,
+                        &self.prev_scalefactor,
+                    );
+                self.lms = lms;
+                (scale_factors, residuals, stereo_mode, prev_scalefactor)
+            }
+            SeaPredictorKind::Lpc { .. } => {
+                let (scale_factors, residuals, stereo_mode, lpc, prev_scalefactor) = self
+                    .run_stereo_trial(
+                        samples,
+                        quant_tab,
+                        dqt,
+                        scalefactor_reciprocals,
+                        &self.lpc,
+                        &self.prev_scalefactor,
+                    );
+                self.lpc = lpc;
+                (scale_factors, residuals, stereo_mode, prev_scalefactor)
+            }
+        };
+
+        self.prev_scalefactor = prev_scalefactor;
 
         EncodedSamples {
             scale_factors,
             residuals,
             residual_bits: vec![],
+            stereo_mode: stereo_mode as u8,
         }
     }
 }
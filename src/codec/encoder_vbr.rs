@@ -1,12 +1,17 @@
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
 use crate::{
     codec::{common::SeaResidualSize, lms::LMS_LEN},
     encoder::EncoderSettings,
 };
 
 use super::{
+    base_encoder::BaseEncoder,
+    chunk::SeaStereoMode,
     common::{EncodedSamples, SeaEncoderTrait, SEA_MAX_CHANNELS},
     dqt::SeaDequantTab,
-    encoder_base::EncoderBase,
+    encoder_cbr::mid_side_transform,
     file::SeaFileHeader,
     lms::SeaLMS,
     qt::SeaQuantTab,
@@ -16,9 +21,10 @@ pub struct VbrEncoder {
     file_header: SeaFileHeader,
     scale_factor_bits: u8,
     scale_factor_frames: u8,
+    joint_stereo: bool,
     vbr_target_bitrate: f32,
     prev_scalefactor: [i32; SEA_MAX_CHANNELS as usize],
-    base_encoder: EncoderBase,
+    base_encoder: BaseEncoder,
     pub lms: Vec<SeaLMS>,
 }
 
@@ -33,10 +39,8 @@ impl VbrEncoder {
             prev_scalefactor: [0; SEA_MAX_CHANNELS as usize],
             lms: SeaLMS::init_vec(file_header.channels as u32),
             scale_factor_frames: encoder_settings.scale_factor_frames,
-            base_encoder: EncoderBase::new(
-                file_header.channels as usize,
-                encoder_settings.scale_factor_bits as usize,
-            ),
+            joint_stereo: encoder_settings.joint_stereo,
+            base_encoder: BaseEncoder::new(),
             vbr_target_bitrate: Self::get_normalized_vbr_bitrate(encoder_settings),
         }
     }
@@ -131,113 +135,278 @@ impl VbrEncoder {
             residual_sizes[*index as usize] = base_residual_bits + 2;
         }
 
-        // count how many times each residual size appears
-        let mut residual_size_counts = vec![0; 9];
-        for i in 0..errors.len() {
-            residual_size_counts[residual_sizes[i] as usize] += 1;
+        residual_sizes
+    }
+
+    /// Pass one: runs the scale-factor/residual search at a fixed `analyze_residual_size` (the
+    /// base rate rounded up by one bit, giving the search enough headroom to see which slices are
+    /// hard to predict) over every scale-factor slice/channel and records each one's best
+    /// quantization error, without committing to any residual size yet. `choose_residual_len_from_errors`
+    /// then turns those errors into the actual per-slice sizes `encode` re-encodes at.
+    fn analyze(
+        &mut self,
+        input_slice: &[i16],
+        quant_tab: &SeaQuantTab,
+        dequant_tab: &mut SeaDequantTab,
+    ) -> Vec<u8> {
+        let channels = self.file_header.channels as usize;
+        let mut errors: Vec<u64> = Vec::with_capacity(input_slice.len() / channels);
+
+        let analyze_residual_size = SeaResidualSize::from((self.vbr_target_bitrate as u8 + 1).min(8));
+
+        let slice_size = self.scale_factor_frames as usize * channels;
+
+        let dqt: &Vec<Vec<i32>> = dequant_tab.get_dqt(analyze_residual_size as usize);
+        let scalefactor_reciprocals =
+            dequant_tab.get_scalefactor_reciprocals(analyze_residual_size as usize);
+
+        let mut lms = self.lms.clone();
+        let mut prev_scalefactor = self.prev_scalefactor;
+
+        let best_residual_bits: &mut [u8] = &mut vec![0u8; slice_size / channels];
+
+        for slice in input_slice.chunks(slice_size) {
+            for channel_offset in 0..channels {
+                let (best_rank, best_lms, best_scalefactor) = self
+                    .base_encoder
+                    .get_residuals_with_best_scalefactor(
+                        channels,
+                        quant_tab,
+                        dqt,
+                        scalefactor_reciprocals,
+                        &slice[channel_offset..],
+                        prev_scalefactor[channel_offset],
+                        &lms[channel_offset],
+                        analyze_residual_size,
+                        self.scale_factor_bits,
+                        best_residual_bits,
+                    );
+
+                prev_scalefactor[channel_offset] = best_scalefactor;
+                lms[channel_offset] = best_lms;
+                errors.push(best_rank);
+            }
         }
 
+        // `target+1`/`target+2` can exceed the 8-bit maximum `SeaResidualSize` supports when the
+        // target is already near the top of the range
+        let mut residual_sizes = self.choose_residual_len_from_errors(input_slice.len(), &errors);
+        for size in residual_sizes.iter_mut() {
+            *size = (*size).min(8);
+        }
         residual_sizes
     }
 
-    fn analyze(&mut self, input_slice: &[i16]) -> Vec<u8> {
-        let mut errors: Vec<u64> = Vec::with_capacity(input_slice.len());
+    /// Binary-searches `residual_bits` so a whole-file `analyze` pass lands within
+    /// `tolerance_kbps` of `target_kbps`, for callers that want to hand VBR a target bitrate
+    /// instead of a target residual width. Returns settings with `residual_bits` adjusted;
+    /// every other field is left as given. Needs the whole input up front (same restriction
+    /// `EncoderSettings::resample` has on `sea_encode`), since a single chunk's worth of audio
+    /// isn't representative enough to size the rest of the file against.
+    pub fn abr_settings(
+        file_header: &SeaFileHeader,
+        encoder_settings: &EncoderSettings,
+        samples: &[i16],
+        quant_tab: &SeaQuantTab,
+        dequant_tab: &mut SeaDequantTab,
+        target_kbps: f32,
+        tolerance_kbps: f32,
+    ) -> EncoderSettings {
+        let channels = file_header.channels as usize;
+        let frames = samples.len() / channels.max(1);
+
+        if frames == 0 {
+            return encoder_settings.clone();
+        }
 
-        let analyze_residual_size = SeaResidualSize::from(self.vbr_target_bitrate as u8 + 1);
+        let mut low = 1.0f32;
+        let mut high = 8.0f32;
+        let mut best = encoder_settings.residual_bits;
 
-        let slice_size = self.scale_factor_frames as usize * self.file_header.channels as usize;
+        // 12 bisections narrow the 1-8 bit starting range to well under a tenth of a bit, which
+        // is plenty for any `tolerance_kbps` worth asking for
+        for _ in 0..12 {
+            let mid = (low + high) / 2.0;
 
-        todo!();
+            let mut trial_settings = encoder_settings.clone();
+            trial_settings.residual_bits = mid;
 
-        // let dqt: &Vec<Vec<i32>> = dequant_tab.get_dqt(analyze_residual_size as usize);
+            let mut encoder = VbrEncoder::new(file_header, &trial_settings);
+            let residual_sizes = encoder.analyze(samples, quant_tab, dequant_tab);
 
-        // let scalefactor_reciprocals =
-        //     dequant_tab.get_scalefactor_reciprocals(analyze_residual_size as usize);
+            let total_bits: u64 = residual_sizes
+                .iter()
+                .map(|&bits| bits as u64 * encoder.scale_factor_frames as u64)
+                .sum();
+            let kbps = total_bits as f32 / frames as f32 * file_header.sample_rate as f32 / 1000.0;
 
-        // let mut lms = self.lms.clone();
-        // let mut prev_scalefactor = self.prev_scalefactor.clone();
+            best = mid;
+            if (kbps - target_kbps).abs() <= tolerance_kbps {
+                break;
+            }
+
+            if kbps > target_kbps {
+                high = mid;
+            } else {
+                low = mid;
+            }
+        }
 
-        // let best_residual_bits: &mut [u8] =
-        //     &mut vec![0u8; input_slice.len() / self.file_header.channels as usize];
+        let mut settings = encoder_settings.clone();
+        settings.residual_bits = best;
+        settings
+    }
 
-        // for (_, input_slice) in input_slice.chunks(slice_size).enumerate() {
-        //     for channel_offset in 0..self.file_header.channels as usize {
-        // let (_best_rank, best_lms, best_scalefactor) =
-        //     self.base_encoder.get_residuals_with_best_scalefactor(
-        //         self.file_header.channels as usize,
-        //         dqt,
-        //         scalefactor_reciprocals,
-        //         &input_slice[channel_offset..],
-        //         prev_scalefactor[channel_offset],
-        //         &lms[channel_offset],
-        //         analyze_residual_size,
-        //         best_residual_bits,
-        //     );
+    /// Nudges `vbr_target_bitrate` toward whatever rate keeps the running average converging on
+    /// `target_kbps`: computes the ideal cumulative bit count for `frames_encoded` frames at
+    /// `sample_rate` and compares it to `bits_emitted` so far, then applies the deficit/surplus as
+    /// a proportional correction (capped so one chunk's feedback can't overshoot the 1.0-8.0
+    /// residual range in a single step). A chunk that spent more than its share pulls the target
+    /// down for the next one; a quiet stretch that spent less lets it rise - `abr_settings` picks
+    /// one static rate from a whole-buffer bisection ahead of time, while this corrects drift
+    /// chunk by chunk as encoding actually progresses, the same feedback a hardware CBR/ABR
+    /// controller uses. Per-chunk allocation still follows `choose_residual_len_from_errors`'s
+    /// local error ranking; this only steers the average the whole file converges toward.
+    ///
+    /// `SeaEncoder`'s own streaming feedback (`EncoderSettings::target_bitrate`) applies this same
+    /// correction against `EncoderSettings::residual_bits` instead of calling this method
+    /// directly, since it doesn't hold the file's `VbrEncoder` instance itself - this is the
+    /// primitive a caller driving a `VbrEncoder` directly (e.g. a custom chunk pipeline) would use.
+    pub fn adjust_target_bitrate(
+        &mut self,
+        target_kbps: f32,
+        sample_rate: u32,
+        frames_encoded: u64,
+        bits_emitted: u64,
+    ) {
+        if frames_encoded == 0 {
+            return;
+        }
 
-        // prev_scalefactor[channel_offset] = best_scalefactor;
-        // lms[channel_offset] = best_lms;
-        // errors.push(_best_rank);
-        //     }
-        // }
+        let ideal_bits = target_kbps * 1000.0 * frames_encoded as f32 / sample_rate as f32;
+        let deficit = ideal_bits - bits_emitted as f32;
+        let correction = (deficit / ideal_bits.max(1.0)).clamp(-0.5, 0.5);
 
-        // self.choose_residual_len_from_errors(input_slice.len(), &errors)
+        self.vbr_target_bitrate = (self.vbr_target_bitrate + correction).clamp(1.0, 8.0);
     }
-}
 
-impl SeaEncoderTrait for VbrEncoder {
-    fn encode(&mut self, samples: &[i16]) -> EncodedSamples {
+    /// Runs `analyze` followed by the real per-slice encoding pass over `samples`, starting from
+    /// `lms`/`prev_scalefactor` rather than `self`'s so a candidate channel layout (e.g. mid/side)
+    /// can be tried without committing its predictor state until `encode` decides it's the winner.
+    /// Returns the encoded data, the predictor state it left off at, and a rank (total coded bits,
+    /// the same quantity `abr_settings` bisects on) so callers can compare candidates.
+    fn encode_channels(
+        &mut self,
+        samples: &[i16],
+        quant_tab: &SeaQuantTab,
+        dequant_tab: &mut SeaDequantTab,
+        lms: &[SeaLMS],
+        prev_scalefactor: &[i32; SEA_MAX_CHANNELS as usize],
+    ) -> (EncodedSamples, Vec<SeaLMS>, [i32; SEA_MAX_CHANNELS as usize], u64) {
+        let channels = self.file_header.channels as usize;
         let mut scale_factors = Vec::<u8>::new();
         let mut residuals = vec![0u8; samples.len()];
 
-        let residual_bits = self.analyze(samples);
+        let residual_bits = self.analyze(samples, quant_tab, dequant_tab);
+
+        let slice_size = self.scale_factor_frames as usize * channels;
 
-        let slice_size = self.scale_factor_frames as usize * self.file_header.channels as usize;
+        let best_residual_bits: &mut [u8] = &mut vec![0u8; slice_size / channels];
 
-        let best_residual_bits: &mut [u8] =
-            &mut vec![0u8; samples.len() / self.file_header.channels as usize];
+        let mut lms = lms.to_vec();
+        let mut prev_scalefactor = *prev_scalefactor;
+        let mut total_bits: u64 = 0;
 
         for (slice_index, input_slice) in samples.chunks(slice_size).enumerate() {
-            for channel_offset in 0..self.file_header.channels as usize {
-                let residual_size = residual_bits
-                    [slice_index * self.file_header.channels as usize + channel_offset]
-                    as usize;
-
-                // let dqt: &Vec<Vec<i32>> = dequant_tab.get_dqt(residual_size);
-                // let scalefactor_reciprocals: &Vec<i32> =
-                //     dequant_tab.get_scalefactor_reciprocals(residual_size);
-
-                // let (_best_rank, best_lms, best_scalefactor) =
-                //     self.base_encoder.get_residuals_with_best_scalefactor(
-                //         self.file_header.channels as usize,
-                //         dqt,
-                //         scalefactor_reciprocals,
-                //         &input_slice[channel_offset..],
-                //         self.prev_scalefactor[channel_offset] as i32,
-                //         &self.lms[channel_offset],
-                //         SeaResidualSize::from(
-                //             residual_bits
-                //                 [slice_index * self.file_header.channels as usize + channel_offset],
-                //         ),
-                //         best_residual_bits,
-                //     );
-
-                // self.prev_scalefactor[channel_offset] = best_scalefactor;
-                // self.lms[channel_offset] = best_lms;
-
-                // scale_factors.push(best_scalefactor as u8);
+            for channel_offset in 0..channels {
+                let residual_size =
+                    SeaResidualSize::from(residual_bits[slice_index * channels + channel_offset]);
+
+                let dqt: &Vec<Vec<i32>> = dequant_tab.get_dqt(residual_size as usize);
+                let scalefactor_reciprocals =
+                    dequant_tab.get_scalefactor_reciprocals(residual_size as usize);
+
+                let (_best_rank, best_lms, best_scalefactor) = self
+                    .base_encoder
+                    .get_residuals_with_best_scalefactor(
+                        channels,
+                        quant_tab,
+                        dqt,
+                        scalefactor_reciprocals,
+                        &input_slice[channel_offset..],
+                        prev_scalefactor[channel_offset],
+                        &lms[channel_offset],
+                        residual_size,
+                        self.scale_factor_bits,
+                        best_residual_bits,
+                    );
+
+                prev_scalefactor[channel_offset] = best_scalefactor;
+                lms[channel_offset] = best_lms;
+                total_bits += residual_size as u64 * best_residual_bits.len() as u64;
+
+                scale_factors.push(best_scalefactor as u8);
+
                 // residuals need to be interleaved
                 for i in 0..best_residual_bits.len() {
-                    residuals[slice_index * slice_size
-                        + i * self.file_header.channels as usize
-                        + channel_offset] = best_residual_bits[i];
+                    residuals[slice_index * slice_size + i * channels + channel_offset] =
+                        best_residual_bits[i];
                 }
             }
         }
 
-        EncodedSamples {
-            scale_factors,
-            residuals,
-            residual_bits,
-        }
+        (
+            EncodedSamples {
+                scale_factors,
+                residuals,
+                residual_bits,
+                stereo_mode: SeaStereoMode::LeftRight as u8,
+            },
+            lms,
+            prev_scalefactor,
+            total_bits,
+        )
+    }
+}
+
+impl SeaEncoderTrait for VbrEncoder {
+    fn encode(
+        &mut self,
+        samples: &[i16],
+        quant_tab: &SeaQuantTab,
+        dequant_tab: &mut SeaDequantTab,
+    ) -> EncodedSamples {
+        let lms = self.lms.clone();
+        let prev_scalefactor = self.prev_scalefactor;
+
+        let (left_right, lr_lms, lr_prev_scalefactor, lr_bits) =
+            self.encode_channels(samples, quant_tab, dequant_tab, &lms, &prev_scalefactor);
+
+        let mid_side = if self.joint_stereo && self.file_header.channels == 2 {
+            mid_side_transform(samples).map(|mid_side_samples| {
+                self.encode_channels(
+                    &mid_side_samples,
+                    quant_tab,
+                    dequant_tab,
+                    &lms,
+                    &prev_scalefactor,
+                )
+            })
+        } else {
+            None
+        };
+
+        let (mut encoded, new_lms, new_prev_scalefactor, stereo_mode) = match mid_side {
+            Some((encoded, ms_lms, ms_prev_scalefactor, ms_bits)) if ms_bits < lr_bits => {
+                (encoded, ms_lms, ms_prev_scalefactor, SeaStereoMode::MidSide)
+            }
+            _ => (left_right, lr_lms, lr_prev_scalefactor, SeaStereoMode::LeftRight),
+        };
+
+        self.lms = new_lms;
+        self.prev_scalefactor = new_prev_scalefactor;
+        encoded.stereo_mode = stereo_mode as u8;
+
+        encoded
     }
 }
@@ -0,0 +1,129 @@
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
+/// Highest LPC order `SeaPredictorKind::Lpc` accepts; high enough to capture most
+/// tonal content without the coefficient search or the header overhead getting out of hand.
+pub const SEA_LPC_MAX_ORDER: usize = 12;
+
+/// Coefficients are stored as Q12 fixed point, the same precision FLAC's `-8` preset settles on.
+const SEA_LPC_SHIFT: u8 = 12;
+
+/// Fixed-order linear predictor, the alternative to `SeaLMS` selected by
+/// `SeaPredictorKind::Lpc`. Coefficients are computed once per chunk via
+/// Levinson-Durbin and held constant for the chunk's duration; only the sample history advances
+/// as `update` is called, mirroring the `predict`/`update` shape `SeaLMS` already exposes.
+#[derive(Debug, Clone)]
+pub struct SeaLpc {
+    order: usize,
+    coefficients: [i32; SEA_LPC_MAX_ORDER],
+    history: [i32; SEA_LPC_MAX_ORDER],
+}
+
+impl SeaLpc {
+    pub fn new(order: usize) -> Self {
+        assert!(order > 0 && order <= SEA_LPC_MAX_ORDER);
+        Self {
+            order,
+            coefficients: [0; SEA_LPC_MAX_ORDER],
+            history: [0; SEA_LPC_MAX_ORDER],
+        }
+    }
+
+    /// Runs autocorrelation + Levinson-Durbin over one channel's samples for this chunk and
+    /// quantizes the resulting coefficients to Q12 fixed point. History starts at zero, same as
+    /// a fresh `SeaLMS`.
+    pub fn from_samples(order: usize, samples: &[i32]) -> Self {
+        let mut lpc = Self::new(order);
+        let scale = (1i64 << SEA_LPC_SHIFT) as f64;
+
+        for (i, c) in levinson_durbin(samples, order).iter().enumerate() {
+            lpc.coefficients[i] = (c * scale).round() as i32;
+        }
+
+        lpc
+    }
+
+    /// Rebuilds a predictor from already-quantized Q12 coefficients, as read back from a
+    /// chunk's header. History starts at zero, matching the encoder's own chunk-start state.
+    pub fn from_coefficients(order: usize, coefficients: [i32; SEA_LPC_MAX_ORDER]) -> Self {
+        let mut lpc = Self::new(order);
+        lpc.coefficients = coefficients;
+        lpc
+    }
+
+    /// The quantized Q12 coefficients, for serializing into the chunk header.
+    pub fn coefficients(&self) -> &[i32; SEA_LPC_MAX_ORDER] {
+        &self.coefficients
+    }
+
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    pub fn predict(&self) -> i32 {
+        let mut acc: i64 = 0;
+        for i in 0..self.order {
+            acc += self.coefficients[i] as i64 * self.history[i] as i64;
+        }
+        (acc >> SEA_LPC_SHIFT) as i32
+    }
+
+    pub fn update(&mut self, reconstructed: i16, _dequantized: i32) {
+        for i in (1..self.order).rev() {
+            self.history[i] = self.history[i - 1];
+        }
+        self.history[0] = reconstructed as i32;
+    }
+
+    // LPC coefficients are fixed for the chunk, so unlike SeaLMS's adaptive weights there's
+    // nothing here to penalize during scalefactor search
+    pub fn get_weights_penalty(&self) -> u64 {
+        0
+    }
+}
+
+/// Returns up to `order` LPC coefficients (`predicted = Σ coefficients[k] * history[k]`) via
+/// the Levinson-Durbin recursion over `samples`'s autocorrelation. Falls back to all-zero
+/// coefficients (equivalent to predicting silence) for silent or too-short input.
+fn levinson_durbin(samples: &[i32], order: usize) -> Vec<f64> {
+    let mut autocorrelation = vec![0.0f64; order + 1];
+    for (lag, slot) in autocorrelation.iter_mut().enumerate() {
+        let mut sum = 0.0;
+        for i in lag..samples.len() {
+            sum += samples[i] as f64 * samples[i - lag] as f64;
+        }
+        *slot = sum;
+    }
+
+    if autocorrelation[0] == 0.0 {
+        return vec![0.0; order];
+    }
+
+    let mut error = autocorrelation[0];
+    let mut coefficients = vec![0.0f64; order];
+
+    for i in 0..order {
+        let mut acc = autocorrelation[i + 1];
+        for j in 0..i {
+            acc -= coefficients[j] * autocorrelation[i - j];
+        }
+        let reflection = acc / error;
+
+        coefficients[i] = reflection;
+        for j in 0..i / 2 {
+            let tmp = coefficients[j];
+            coefficients[j] -= reflection * coefficients[i - 1 - j];
+            coefficients[i - 1 - j] -= reflection * tmp;
+        }
+        if i % 2 == 1 {
+            coefficients[i / 2] -= reflection * coefficients[i / 2];
+        }
+
+        error *= 1.0 - reflection * reflection;
+        if error <= 0.0 {
+            break;
+        }
+    }
+
+    coefficients
+}
@@ -0,0 +1,443 @@
+use std::f64::consts::PI;
+
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
+// half the number of input samples a single output sample convolves over, on each side
+const HALF_TAPS: usize = 16;
+const TAP_COUNT: usize = HALF_TAPS * 2;
+const PHASES: usize = 256;
+const KAISER_BETA: f64 = 8.0;
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// `src_rate / dst_rate` reduced to lowest terms, so the fractional position accumulator below
+/// repeats on a short, exact cycle instead of drifting under floating point error.
+pub struct Fraction {
+    pub num: u32,
+    pub den: u32,
+}
+
+impl Fraction {
+    pub fn new(src_rate: u32, dst_rate: u32) -> Self {
+        let divisor = gcd(src_rate, dst_rate).max(1);
+        Self {
+            num: src_rate / divisor,
+            den: dst_rate / divisor,
+        }
+    }
+}
+
+struct FracPos {
+    ipos: usize,
+    frac: u32,
+}
+
+impl FracPos {
+    fn advance(&mut self, ratio: &Fraction) {
+        self.frac += ratio.num;
+        while self.frac >= ratio.den {
+            self.frac -= ratio.den;
+            self.ipos += 1;
+        }
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-9 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+// modified Bessel function of the first kind, order 0, via the standard power series
+fn bessel_i0(x: f64) -> f64 {
+    let mut i0 = 1.0;
+    let mut term = 1.0;
+    let arg = (x * x) / 4.0;
+    let mut n = 1.0;
+
+    loop {
+        term *= arg / (n * n);
+        i0 += term;
+        if term < 1e-10 {
+            break;
+        }
+        n += 1.0;
+    }
+
+    i0
+}
+
+fn kaiser(x: f64, half_width: f64) -> f64 {
+    if x.abs() > half_width {
+        return 0.0;
+    }
+    let ratio = x / half_width;
+    bessel_i0(KAISER_BETA * (1.0 - ratio * ratio).sqrt()) / bessel_i0(KAISER_BETA)
+}
+
+/// Polyphase windowed-sinc resampler: a bank of `PHASES` coefficient sets is precomputed once,
+/// each `sinc(x) * kaiser(x)` evaluated at that phase's fractional offset and normalized so its
+/// taps sum to 1, then every output sample just picks the phase at or just below its fractional
+/// position and convolves.
+pub struct Resampler {
+    ratio: Fraction,
+    taps_bank: Vec<[f64; TAP_COUNT]>,
+    pos: FracPos,
+    // per-channel tail of the last TAP_COUNT input samples, carried across calls so chunk
+    // boundaries don't produce seams
+    history: Vec<Vec<i16>>,
+}
+
+impl Resampler {
+    pub fn new(src_rate: u32, dst_rate: u32, channels: usize) -> Self {
+        let ratio = Fraction::new(src_rate, dst_rate);
+        let half_width = HALF_TAPS as f64;
+
+        let taps_bank: Vec<[f64; TAP_COUNT]> = (0..PHASES)
+            .map(|phase| {
+                let frac = phase as f64 / PHASES as f64;
+                let mut coeffs = [0f64; TAP_COUNT];
+                let mut sum = 0.0;
+
+                for (k, coeff) in coeffs.iter_mut().enumerate() {
+                    let x = (k as f64 - HALF_TAPS as f64 + 1.0) - frac;
+                    let w = sinc(x) * kaiser(x, half_width);
+                    *coeff = w;
+                    sum += w;
+                }
+
+                if sum != 0.0 {
+                    for coeff in coeffs.iter_mut() {
+                        *coeff /= sum;
+                    }
+                }
+
+                coeffs
+            })
+            .collect();
+
+        Self {
+            ratio,
+            taps_bank,
+            pos: FracPos { ipos: 0, frac: 0 },
+            history: vec![vec![0i16; TAP_COUNT]; channels],
+        }
+    }
+
+    /// Resamples one block of interleaved `i16` samples, carrying enough history across calls
+    /// that consecutive blocks stitch together seamlessly.
+    pub fn process(&mut self, input: &[i16], channels: usize) -> Vec<i16> {
+        assert_eq!(self.history.len(), channels);
+
+        // `extended[c]` is this channel's carried history followed by the new input, so indices
+        // into it already line up with `self.pos.ipos` counted from the start of history.
+        let mut extended: Vec<Vec<i16>> = (0..channels)
+            .map(|c| {
+                let mut buf = self.history[c].clone();
+                buf.extend(input.iter().skip(c).step_by(channels));
+                buf
+            })
+            .collect();
+
+        let mut output = Vec::new();
+
+        loop {
+            // index into `extended`, where TAP_COUNT history samples precede the new input; the
+            // taps read `center - HALF_TAPS + 1 ..= center + HALF_TAPS`
+            let center = self.pos.ipos + HALF_TAPS;
+            if center + HALF_TAPS >= extended[0].len() {
+                break;
+            }
+
+            let phase = ((self.pos.frac as u64 * PHASES as u64) / self.ratio.den as u64) as usize;
+            let taps = &self.taps_bank[phase.min(PHASES - 1)];
+
+            for channel_samples in extended.iter() {
+                let mut acc = 0.0;
+                for (k, tap) in taps.iter().enumerate() {
+                    acc += channel_samples[center - HALF_TAPS + 1 + k] as f64 * tap;
+                }
+                output.push(acc.round().clamp(i16::MIN as f64, i16::MAX as f64) as i16);
+            }
+
+            self.pos.advance(&self.ratio);
+        }
+
+        // carry the tail needed for the next call, and rebase `ipos` so it still points at the
+        // same logical input sample relative to the new (shorter) history
+        let tail_start = extended[0].len().saturating_sub(TAP_COUNT);
+        for (c, channel_samples) in extended.iter_mut().enumerate() {
+            self.history[c] = channel_samples.split_off(tail_start);
+        }
+        self.pos.ipos = self.pos.ipos.saturating_sub(tail_start);
+
+        output
+    }
+
+    /// Drains the remaining output trailing the last real input sample, for a caller that knows
+    /// no more input is coming (e.g. at end of stream). `process` only emits an output sample once
+    /// `HALF_TAPS` input samples past it have arrived, so without this the last bit of audio -
+    /// shorter than that - would otherwise never come out. Implemented by feeding `HALF_TAPS`
+    /// frames of silence, the usual way to flush a FIR-style convolution's tail.
+    pub fn flush(&mut self, channels: usize) -> Vec<i16> {
+        self.process(&vec![0i16; HALF_TAPS * channels], channels)
+    }
+}
+
+// 4-point Catmull-Rom cubic convolution, e.g. http://www.paulinternet.nl/?page=bicubic
+fn catmull_rom(s: [f64; 4], t: f64) -> f64 {
+    s[1] + 0.5
+        * t
+        * (s[2] - s[0]
+            + t * (2.0 * s[0] - 5.0 * s[1] + 4.0 * s[2] - s[3]
+                + t * (3.0 * (s[1] - s[2]) + s[3] - s[0])))
+}
+
+/// Lightweight alternative to `Resampler`: 4-point Catmull-Rom cubic interpolation instead of a
+/// windowed-sinc convolution. Lower quality than `Resampler` but only needs basic arithmetic (no
+/// `sin`/`sqrt`), so it works without the `std` feature, and costs a fraction of the CPU per
+/// output sample - a better fit for realtime playback on constrained targets.
+pub struct CubicResampler {
+    // `src_rate / dst_rate`, the amount `pos` advances per output sample
+    step: f64,
+    // fractional position of the next output sample, measured in input samples since the start
+    // of the most recent `process` call's input (the 3 carried history samples sit before 0.0)
+    pos: f64,
+    // per-channel tail of the last 3 input samples, carried across calls so chunk boundaries
+    // don't produce seams
+    history: Vec<[i16; 3]>,
+}
+
+impl CubicResampler {
+    pub fn new(src_rate: u32, dst_rate: u32, channels: usize) -> Self {
+        Self {
+            step: src_rate as f64 / dst_rate as f64,
+            pos: 0.0,
+            history: vec![[0i16; 3]; channels],
+        }
+    }
+
+    /// Resamples one block of interleaved `i16` samples, carrying the last 3 samples per channel
+    /// across calls so consecutive blocks stitch together seamlessly.
+    pub fn process(&mut self, input: &[i16], channels: usize) -> Vec<i16> {
+        assert_eq!(self.history.len(), channels);
+
+        let frames = input.len() / channels;
+        let mut output = Vec::new();
+
+        // index `i` into this channel's samples, where -3..-1 are carried history and 0.. is
+        // `input`; s[i-1..=i+2] are the 4 points the cubic kernel interpolates between
+        let sample_at = |channel: usize, i: isize| -> f64 {
+            if i < 0 {
+                self.history[channel][(3 + i) as usize] as f64
+            } else {
+                input[i as usize * channels + channel] as f64
+            }
+        };
+
+        // only emit while s[i + 2] is inside this call's input; later positions wait for the next
+        // call, where they fall within the carried history
+        while (self.pos.floor() as isize) + 2 < frames as isize {
+            let i = self.pos.floor() as isize;
+            let t = self.pos - i as f64;
+
+            for channel in 0..channels {
+                let s = [
+                    sample_at(channel, i - 1),
+                    sample_at(channel, i),
+                    sample_at(channel, i + 1),
+                    sample_at(channel, i + 2),
+                ];
+                let value = catmull_rom(s, t).round();
+                output.push(value.clamp(i16::MIN as f64, i16::MAX as f64) as i16);
+            }
+
+            self.pos += self.step;
+        }
+
+        self.pos -= frames as f64;
+
+        if frames >= 3 {
+            for (channel, history) in self.history.iter_mut().enumerate() {
+                *history = [
+                    input[(frames - 3) * channels + channel],
+                    input[(frames - 2) * channels + channel],
+                    input[(frames - 1) * channels + channel],
+                ];
+            }
+        } else {
+            // fewer than 3 new frames arrived - shift in what we have and keep the rest of the
+            // existing history
+            for frame in 0..frames {
+                for (channel, history) in self.history.iter_mut().enumerate() {
+                    history.rotate_left(1);
+                    history[2] = input[frame * channels + channel];
+                }
+            }
+        }
+
+        output
+    }
+
+    /// Drains the remaining output trailing the last real input sample, for a caller that knows
+    /// no more input is coming. Same idea as `Resampler::flush`, just with 3 frames of silence -
+    /// the cubic kernel only ever looks two frames past `pos.floor()`.
+    pub fn flush(&mut self, channels: usize) -> Vec<i16> {
+        self.process(&vec![0i16; 3 * channels], channels)
+    }
+}
+
+/// Which kernel `BufferResampler` (and `EncoderSettings::resample`) converts sample rates with.
+/// `Cubic` and `Polyphase` reuse `CubicResampler`/`Resampler` above; `Nearest`/`Linear` are
+/// cheap enough to not need a dedicated carried-history struct, and `Cosine` is the usual
+/// raised-cosine variant of linear blending. `Cosine` and `Polyphase` need `cos`/`sin`/`sqrt`,
+/// unavailable in bare `core`, so they require the `std` feature like `Resampler` does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterpolationMode {
+    Nearest,
+    Linear,
+    #[cfg(feature = "std")]
+    Cosine,
+    Cubic,
+    #[cfg(feature = "std")]
+    Polyphase,
+}
+
+/// Fixed-point-style fractional cursor for the `Nearest`/`Linear`/`Cosine` kernels: `pos` is the
+/// position of the next output sample, measured in source samples since the start of the current
+/// `process` call, advanced by `step = src_rate / dst_rate` per output sample. Every kernel here
+/// only needs the sample immediately before and after `pos`, so just the single sample preceding
+/// a call's input is carried across calls (cf. `CubicResampler`'s 3-sample history).
+struct CursorResampler {
+    mode: InterpolationMode,
+    step: f64,
+    pos: f64,
+    // per-channel last sample from the previous call, used when `pos.floor() < 0`
+    prev: Vec<i16>,
+}
+
+impl CursorResampler {
+    fn new(mode: InterpolationMode, src_rate: u32, dst_rate: u32, channels: usize) -> Self {
+        Self {
+            mode,
+            step: src_rate as f64 / dst_rate as f64,
+            pos: 0.0,
+            prev: vec![0i16; channels],
+        }
+    }
+
+    fn process(&mut self, input: &[i16], channels: usize) -> Vec<i16> {
+        assert_eq!(self.prev.len(), channels);
+
+        let frames = input.len() / channels;
+        if frames == 0 {
+            return Vec::new();
+        }
+        let mut output = Vec::new();
+
+        let sample_at = |channel: usize, i: isize| -> f64 {
+            if i < 0 {
+                self.prev[channel] as f64
+            } else if (i as usize) < frames {
+                input[i as usize * channels + channel] as f64
+            } else {
+                // clamp to the last sample available in this call's input
+                input[(frames - 1) * channels + channel] as f64
+            }
+        };
+
+        while self.pos < frames as f64 {
+            let i = self.pos.floor() as isize;
+            let t = self.pos - i as f64;
+
+            for channel in 0..channels {
+                let a = sample_at(channel, i);
+                let b = sample_at(channel, i + 1);
+
+                let value = match self.mode {
+                    InterpolationMode::Nearest => {
+                        if t < 0.5 {
+                            a
+                        } else {
+                            b
+                        }
+                    }
+                    InterpolationMode::Linear => a + (b - a) * t,
+                    #[cfg(feature = "std")]
+                    InterpolationMode::Cosine => {
+                        let weight = (1.0 - (PI * t).cos()) / 2.0;
+                        a + (b - a) * weight
+                    }
+                    InterpolationMode::Cubic => unreachable!("Cubic uses CubicResampler"),
+                    #[cfg(feature = "std")]
+                    InterpolationMode::Polyphase => unreachable!("Polyphase uses Resampler"),
+                };
+
+                output.push(value.round().clamp(i16::MIN as f64, i16::MAX as f64) as i16);
+            }
+
+            self.pos += self.step;
+        }
+
+        self.pos -= frames as f64;
+
+        for (channel, prev) in self.prev.iter_mut().enumerate() {
+            *prev = input[(frames - 1) * channels + channel];
+        }
+
+        output
+    }
+}
+
+enum BufferResamplerKind {
+    Cursor(CursorResampler),
+    Cubic(CubicResampler),
+    #[cfg(feature = "std")]
+    Polyphase(Resampler),
+}
+
+/// One-shot sample-rate converter over a whole in-memory buffer, used by `sea_encode`'s
+/// `EncoderSettings::resample` to retarget a source rate before encoding. Unlike
+/// `CubicResampler`/`Resampler`, which are built to carry history across repeated `process`
+/// calls on a chunked stream, this wraps a single call since `sea_encode` already has the full
+/// input up front.
+pub struct BufferResampler {
+    kind: BufferResamplerKind,
+}
+
+impl BufferResampler {
+    pub fn new(mode: InterpolationMode, src_rate: u32, dst_rate: u32, channels: usize) -> Self {
+        let kind = match mode {
+            InterpolationMode::Cubic => {
+                BufferResamplerKind::Cubic(CubicResampler::new(src_rate, dst_rate, channels))
+            }
+            #[cfg(feature = "std")]
+            InterpolationMode::Polyphase => {
+                BufferResamplerKind::Polyphase(Resampler::new(src_rate, dst_rate, channels))
+            }
+            _ => BufferResamplerKind::Cursor(CursorResampler::new(
+                mode, src_rate, dst_rate, channels,
+            )),
+        };
+
+        Self { kind }
+    }
+
+    pub fn process(&mut self, input: &[i16], channels: usize) -> Vec<i16> {
+        match &mut self.kind {
+            BufferResamplerKind::Cursor(resampler) => resampler.process(input, channels),
+            BufferResamplerKind::Cubic(resampler) => resampler.process(input, channels),
+            #[cfg(feature = "std")]
+            BufferResamplerKind::Polyphase(resampler) => resampler.process(input, channels),
+        }
+    }
+}
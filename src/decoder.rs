@@ -1,23 +1,83 @@
-use std::io;
+#[cfg(not(feature = "std"))]
+use alloc::vec;
 
 use bytemuck::cast_slice;
 
-use crate::codec::{
-    common::SeaError,
-    file::{SeaFile, SeaFileHeader},
+#[cfg(feature = "std")]
+use crate::codec::resample::Resampler;
+use crate::{
+    codec::{
+        channels::ChannelMixer,
+        common::SeaError,
+        file::{SeaFile, SeaFileHeader},
+        resample::CubicResampler,
+    },
+    encoder::SeaSampleFormat,
+    io::{Read, Write},
 };
 
+/// Which algorithm a `SeaDecoder` resamples its output with: `Sinc` is set via `set_resample`,
+/// `Cubic` via `with_resample`.
+/// `Sinc` (windowed-sinc convolution) is higher quality but needs transcendental math that
+/// `core` doesn't provide, so it's only available with the `std` feature; `Cubic` (4-point
+/// Catmull-Rom) is lower quality but cheap enough for realtime use and works without `std`.
+enum DecoderResampler {
+    #[cfg(feature = "std")]
+    Sinc(Resampler),
+    Cubic(CubicResampler),
+}
+
+impl DecoderResampler {
+    fn process(&mut self, samples: &[i16], channels: usize) -> Vec<i16> {
+        match self {
+            #[cfg(feature = "std")]
+            DecoderResampler::Sinc(resampler) => resampler.process(samples, channels),
+            DecoderResampler::Cubic(resampler) => resampler.process(samples, channels),
+        }
+    }
+}
+
+/// One entry in the in-memory seek index `decode_frame_resilient` builds up as it visits chunks
+/// sequentially: a chunk's first frame paired with the byte offset its header was read from.
+/// `seek_to_frame` uses a recorded offset when it has one, and otherwise falls back to
+/// `header_size + chunk_index * chunk_size`, which holds because every chunk but the last is
+/// `chunk_size` bytes (`SeaEncoder::encode_frame` asserts this).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeekIndexEntry {
+    pub frame: usize,
+    pub byte_offset: u64,
+}
+
 pub struct SeaDecoder<R, W> {
     reader: R,
     writer: W,
     file: SeaFile,
     frames_read: usize,
+    verify_crc: bool,
+    resampler: Option<DecoderResampler>,
+    // the rate `resampler` is rendering at, reported by `get_header` in place of the file's
+    // native `sample_rate`; `None` alongside `resampler: None` when resampling is off
+    resample_rate: Option<u32>,
+    // remixes each decoded frame from the file's stored channel layout to `channel_mixer`'s
+    // output layout, e.g. to downmix surround content at playback time; `None` passes frames
+    // through unchanged. See `set_channel_mixer`/`with_channel_mixer`.
+    channel_mixer: Option<ChannelMixer>,
+    // last successfully decoded frame, native channel layout, pre-resample/mixer; `resync`'s
+    // concealment repeats this across a chunk it can't recover. Empty until the first frame
+    // decodes.
+    last_frame: Vec<i16>,
+    // built by `decode_frame_resilient`; see `SeekIndexEntry`.
+    seek_index: Vec<SeekIndexEntry>,
+    // container format written out for each decoded sample, via `SeaSampleFormat::from_i16`;
+    // `I16` (the default) writes the codec's native domain straight through. See
+    // `set_output_format`/`with_output_format`.
+    output_format: SeaSampleFormat,
 }
 
 impl<R, W> SeaDecoder<R, W>
 where
-    R: io::Read,
-    W: io::Write,
+    R: Read,
+    W: Write,
 {
     pub fn new(mut reader: R, writer: W) -> Result<Self, SeaError> {
         let file = SeaFile::from_reader(&mut reader)?;
@@ -27,9 +87,83 @@ where
             writer,
             file,
             frames_read: 0,
+            verify_crc: false,
+            resampler: None,
+            resample_rate: None,
+            channel_mixer: None,
+            last_frame: Vec::new(),
+            seek_index: Vec::new(),
+            output_format: SeaSampleFormat::I16,
         })
     }
 
+    /// Widens every decoded sample out to `format` (see `SeaSampleFormat::from_i16`) instead of
+    /// writing the codec's native i16 PCM straight through. This doesn't recover any precision
+    /// lost at encode time - the internal pipeline is i16 end to end - it just lets a caller that
+    /// knows its source was e.g. 24-bit get 24-bit container samples back out instead of being
+    /// forced to consume 16-bit PCM. `SeaWavDecoder::with_output_format` uses this to pick the
+    /// `fmt ` chunk it writes.
+    pub fn set_output_format(&mut self, format: SeaSampleFormat) {
+        self.output_format = format;
+    }
+
+    /// Builder-style variant of `set_output_format`.
+    pub fn with_output_format(mut self, format: SeaSampleFormat) -> Self {
+        self.output_format = format;
+        self
+    }
+
+    /// When enabled, each chunk's CRC-8 header and CRC-16 footer are recomputed and checked
+    /// before it is decoded, and `SeaError::ChecksumMismatch` is returned on mismatch instead of
+    /// decoding the corrupted chunk. Off by default, in which case chunks are decoded without
+    /// either check. Streams in the earlier chunk layout, written before these checksums existed,
+    /// are not readable in either mode (see `SeaChunk::from_slice`).
+    pub fn set_verify(&mut self, verify_crc: bool) {
+        self.verify_crc = verify_crc;
+    }
+
+    /// Renders decoded audio at `target_rate` instead of the file's native `sample_rate`, via a
+    /// polyphase windowed-sinc resampler applied per chunk. Pass `None` to disable and emit the
+    /// native rate again. Requires the `std` feature; no_std decoders can use `with_resample`'s
+    /// cubic resampler instead.
+    #[cfg(feature = "std")]
+    pub fn set_resample(&mut self, target_rate: Option<u32>) {
+        self.resampler = target_rate.map(|target_rate| {
+            DecoderResampler::Sinc(Resampler::new(
+                self.file.header.sample_rate,
+                target_rate,
+                self.file.header.channels as usize,
+            ))
+        });
+        self.resample_rate = target_rate;
+    }
+
+    /// Builder-style variant of resampling to `target_rate`: renders with the cheap 4-point
+    /// Catmull-Rom cubic resampler (see `CubicResampler`) rather than `set_resample`'s
+    /// windowed-sinc one, at lower quality but without needing the `std` feature.
+    pub fn with_resample(mut self, target_rate: u32) -> Self {
+        self.resampler = Some(DecoderResampler::Cubic(CubicResampler::new(
+            self.file.header.sample_rate,
+            target_rate,
+            self.file.header.channels as usize,
+        )));
+        self.resample_rate = Some(target_rate);
+        self
+    }
+
+    /// Remixes decoded audio from the file's stored channel layout through `mixer` (e.g. folding
+    /// surround content down to stereo) instead of emitting it unchanged. Pass `None` to disable
+    /// remixing. `mixer.input_channels()` must match the file's stored channel count.
+    pub fn set_channel_mixer(&mut self, mixer: Option<ChannelMixer>) {
+        self.channel_mixer = mixer;
+    }
+
+    /// Builder-style variant of `set_channel_mixer`.
+    pub fn with_channel_mixer(mut self, mixer: ChannelMixer) -> Self {
+        self.channel_mixer = Some(mixer);
+        self
+    }
+
     pub fn decode_frame(&mut self) -> Result<bool, SeaError> {
         if self.file.header.total_frames != 0
             && (self.file.header.total_frames as usize) <= self.frames_read
@@ -43,21 +177,57 @@ where
             None
         };
 
-        let reader_res = self
-            .file
-            .samples_from_reader(&mut self.reader, remaining_frames)?;
+        let reader_res =
+            self.file
+                .samples_from_reader(&mut self.reader, remaining_frames, self.verify_crc)?;
 
         match reader_res {
             Some(samples) => {
                 self.frames_read += samples.len() / self.file.header.channels as usize;
-                let samples_u8: &[u8] = cast_slice(&samples);
-                self.writer.write_all(samples_u8)?;
+
+                if let Some(last) = samples
+                    .chunks_exact(self.file.header.channels as usize)
+                    .last()
+                {
+                    self.last_frame = last.to_vec();
+                }
+
+                self.write_samples(samples)?;
                 Ok(true)
             }
             None => Ok(false),
         }
     }
 
+    /// Runs the resample/channel-mixer pipeline (if configured) over one block of natively
+    /// decoded samples and writes the result. Shared by `decode_frame` and, for seekable readers,
+    /// `resync`'s concealment, so concealed audio goes through the same pipeline as real audio.
+    fn write_samples(&mut self, samples: Vec<i16>) -> Result<(), SeaError> {
+        let samples = match &mut self.resampler {
+            Some(resampler) => resampler.process(&samples, self.file.header.channels as usize),
+            None => samples,
+        };
+
+        let samples = match &self.channel_mixer {
+            Some(mixer) => mixer.process(&samples),
+            None => samples,
+        };
+
+        if matches!(self.output_format, SeaSampleFormat::I16) {
+            let samples_u8: &[u8] = cast_slice(&samples);
+            self.writer.write_all(samples_u8)?;
+        } else {
+            let bytes_per_sample = self.output_format.bytes_per_sample();
+            let mut out = vec![0u8; samples.len() * bytes_per_sample];
+            for (sample, chunk) in samples.iter().zip(out.chunks_exact_mut(bytes_per_sample)) {
+                self.output_format.from_i16(*sample, chunk);
+            }
+            self.writer.write_all(&out)?;
+        }
+
+        Ok(())
+    }
+
     pub fn flush(&mut self) {
         let _ = self.writer.flush();
     }
@@ -67,7 +237,221 @@ where
         Ok(())
     }
 
+    /// Returns the file's header, with `sample_rate` overridden to the active resample target
+    /// (see `set_resample`/`with_resample`) and `channels` overridden to the active channel
+    /// mixer's output count (see `set_channel_mixer`/`with_channel_mixer`), so front-ends like
+    /// `SeaWavDecoder` describe the stream they'll actually receive from `decode_frame`, not the
+    /// file's native layout.
     pub fn get_header(&self) -> SeaFileHeader {
-        self.file.header.clone()
+        let mut header = self.file.header.clone();
+        if let Some(resample_rate) = self.resample_rate {
+            header.sample_rate = resample_rate;
+        }
+        if let Some(mixer) = &self.channel_mixer {
+            header.channels = mixer.output_channels() as u8;
+        }
+        header
+    }
+
+    /// Gives front-ends like `SeaWavDecoder` a place to write container bytes (e.g. a WAV
+    /// header) ahead of the decoded PCM this decoder writes during `decode_frame`.
+    pub fn writer_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+}
+
+/// Everything needed to resume decoding at a given point in the stream: just the frame
+/// position, since each chunk is self-contained (it carries its own LMS/scale-factor state in
+/// its header rather than relying on the previous chunk's) so only its byte offset needs
+/// locating - no chunk before it needs decoding. Every chunk but the last is the same size, so
+/// `seek_to_frame` can compute that offset directly; when the in-memory index
+/// `decode_frame_resilient` builds while decoding (see `SeekIndexEntry`) already holds the
+/// target chunk, it uses that recorded offset instead. A
+/// host wanting to save/resume a playback position cheaply (e.g. an intro/loop playback engine)
+/// can stash this between sessions and hand it to `set_state` on a fresh `SeaDecoder` over the
+/// same file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeaDecoderState {
+    pub frame: usize,
+}
+
+impl<R, W> SeaDecoder<R, W>
+where
+    R: Read,
+    W: Write,
+{
+    /// Captures the current playback position so it can be restored later via `set_state`.
+    pub fn get_state(&self) -> SeaDecoderState {
+        SeaDecoderState {
+            frame: self.frames_read,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R, W> SeaDecoder<R, W>
+where
+    R: Read + std::io::Seek,
+    W: Write,
+{
+    /// Restores a position previously captured with `get_state`, equivalent to
+    /// `seek_to_frame(state.frame)`.
+    pub fn set_state(&mut self, state: SeaDecoderState) -> Result<(), SeaError> {
+        self.seek_to_frame(state.frame)
+    }
+
+    /// Jumps to the chunk containing `frame` and resumes sequential decoding from there via
+    /// `decode_frame`. Each chunk restores its own LMS/scale-factor state from its header, so
+    /// this only needs to locate the right chunk and discard the leading samples within it
+    /// that come before `frame` - no earlier chunks need to be decoded.
+    pub fn seek_to_frame(&mut self, frame: usize) -> Result<(), SeaError> {
+        let frames_per_chunk = self.file.header.frames_per_chunk as usize;
+        let channels = self.file.header.channels as usize;
+
+        let chunk_index = frame / frames_per_chunk;
+        let chunk_start_frame = chunk_index * frames_per_chunk;
+
+        let chunk_offset = match self
+            .seek_index
+            .iter()
+            .rev()
+            .find(|entry| entry.frame == chunk_start_frame)
+        {
+            // an earlier sequential decode already recorded the offset this chunk was read from
+            Some(entry) => entry.byte_offset as usize,
+            // not indexed yet: fall back to the fixed chunk-size arithmetic
+            None => {
+                let header_size = self.file.header.serialize().len();
+                header_size + chunk_index * self.file.header.chunk_size as usize
+            }
+        };
+
+        self.reader
+            .seek(std::io::SeekFrom::Start(chunk_offset as u64))?;
+        self.frames_read = chunk_start_frame;
+
+        let leading_frames_to_discard = frame - self.frames_read;
+        if leading_frames_to_discard == 0 {
+            return Ok(());
+        }
+
+        let remaining_frames = if self.file.header.total_frames > 0 {
+            Some(self.file.header.total_frames as usize - self.frames_read)
+        } else {
+            None
+        };
+
+        let samples = self
+            .file
+            .samples_from_reader(&mut self.reader, remaining_frames, self.verify_crc)?
+            .ok_or(SeaError::InvalidFrame)?;
+
+        self.frames_read += samples.len() / channels;
+
+        let trimmed = samples[leading_frames_to_discard * channels..].to_vec();
+        self.write_samples(trimmed)?;
+
+        Ok(())
+    }
+
+    /// The seek index built so far; see `SeekIndexEntry`. Empty until at least one chunk has been
+    /// decoded via `decode_frame_resilient`, and only ever as complete as the portion of the
+    /// stream that's been visited - there's no persisted file-level index to read it from up
+    /// front (only `decode_frame`'s plain, non-indexing path exists before this method runs).
+    pub fn seek_index(&self) -> &[SeekIndexEntry] {
+        &self.seek_index
+    }
+
+    /// Like `decode_frame`, but notes this chunk's starting frame and byte offset before decoding
+    /// and, once the chunk decodes without error, records them into the seek index (see
+    /// `seek_index`/`SeekIndexEntry`); and it resyncs instead of
+    /// returning `SeaError::ChecksumMismatch`: it scans forward for the next plausible chunk
+    /// boundary and conceals the lost audio rather than aborting the stream. Requires
+    /// `set_verify(true)`, since `decode_frame` never reports a mismatch to resync from
+    /// otherwise. Prefer this over `decode_frame` for any stream that could be truncated or
+    /// bit-damaged in transit (e.g. read over a flaky network) rather than a trusted local file.
+    pub fn decode_frame_resilient(&mut self) -> Result<bool, SeaError> {
+        let byte_offset = self.reader.stream_position()?;
+        let frame = self.frames_read;
+
+        match self.decode_frame() {
+            Ok(advanced) => {
+                self.seek_index.push(SeekIndexEntry { frame, byte_offset });
+                Ok(advanced)
+            }
+            Err(SeaError::ChecksumMismatch) => self.resync(frame),
+            Err(other) => Err(other),
+        }
+    }
+
+    /// Scans forward, one byte at a time, from wherever the failed chunk left the reader for the
+    /// next offset whose first byte is a plausible `SeaChunkType` tag - the cheapest resync check
+    /// available without re-deriving a chunk's full packed length (which needs its own CRC-8 to
+    /// trust), at the cost of occasionally resyncing a byte or two later than the true boundary
+    /// on an unlucky false positive. The lost chunk itself is concealed by holding the last
+    /// decoded frame for its duration rather than dropping to silence or aborting.
+    fn resync(&mut self, lost_chunk_frame: usize) -> Result<bool, SeaError> {
+        let frames_per_chunk = self.file.header.frames_per_chunk as usize;
+
+        // how many bytes to search before giving up and seeking to EOF
+        const RESYNC_WINDOW: u64 = 1 << 20;
+
+        let search_start = self.reader.stream_position()?;
+        let mut candidate = search_start;
+
+        let found_offset = loop {
+            if candidate - search_start > RESYNC_WINDOW {
+                break None;
+            }
+
+            self.reader.seek(std::io::SeekFrom::Start(candidate))?;
+            let mut type_byte = [0u8; 1];
+            if self.reader.read_exact(&mut type_byte).is_err() {
+                break None;
+            }
+
+            if matches!(type_byte[0], 0x01..=0x04) {
+                break Some(candidate);
+            }
+
+            candidate += 1;
+        };
+
+        let concealed_frames = match self.file.header.total_frames {
+            0 => frames_per_chunk,
+            total_frames => frames_per_chunk.min((total_frames as usize).saturating_sub(lost_chunk_frame)),
+        };
+
+        self.conceal(concealed_frames)?;
+        self.frames_read = lost_chunk_frame + concealed_frames;
+
+        match found_offset {
+            Some(offset) => {
+                self.reader.seek(std::io::SeekFrom::Start(offset))?;
+                Ok(true)
+            }
+            None => {
+                self.reader.seek(std::io::SeekFrom::End(0))?;
+                Ok(false)
+            }
+        }
+    }
+
+    /// Writes `frames` frames of concealment audio through the usual resample/mixer pipeline
+    /// (see `write_samples`), holding the last successfully decoded frame constant - a cheap
+    /// "freeze" conceal, the same fallback most realtime decoders reach for on a single lost
+    /// frame, since a sudden drop to silence is more audible than a brief held note.
+    fn conceal(&mut self, frames: usize) -> Result<(), SeaError> {
+        if frames == 0 || self.last_frame.is_empty() {
+            return Ok(());
+        }
+
+        let channels = self.last_frame.len();
+        let mut concealed = vec![0i16; frames * channels];
+        for frame in concealed.chunks_exact_mut(channels) {
+            frame.copy_from_slice(&self.last_frame);
+        }
+
+        self.write_samples(concealed)
     }
 }
@@ -1,18 +1,192 @@
-use std::{io, rc::Rc};
+#[cfg(feature = "std")]
+use std::rc::Rc;
+
+#[cfg(not(feature = "std"))]
+use alloc::rc::Rc;
+
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap;
 
 use bytemuck::cast_slice;
 
-use crate::codec::{
-    common::{read_max_or_zero, SeaError},
-    file::{SeaFile, SeaFileHeader},
+#[cfg(feature = "std")]
+use crate::codec::resample::Resampler;
+use crate::{
+    codec::{
+        channels::ChannelMixer,
+        common::{clamp_i16, clamp_sample, read_max_or_zero, SeaError},
+        file::{SeaFile, SeaFileHeader},
+        lpc::SEA_LPC_MAX_ORDER,
+        resample::{CubicResampler, InterpolationMode},
+    },
+    io::{Read, Write},
 };
 
+/// Which algorithm `SeaEncoder` resamples its input with, when `EncoderSettings::input_sample_rate`
+/// differs from the stream's `sample_rate`. Same split as `decoder::DecoderResampler`: windowed-sinc
+/// under `std`, falling back to the lighter Catmull-Rom kernel without it, since `Resampler` needs
+/// transcendental math `core` doesn't provide.
+enum EncoderResampler {
+    #[cfg(feature = "std")]
+    Sinc(Resampler),
+    Cubic(CubicResampler),
+}
+
+impl EncoderResampler {
+    fn new(src_rate: u32, dst_rate: u32, channels: usize) -> Self {
+        #[cfg(feature = "std")]
+        {
+            EncoderResampler::Sinc(Resampler::new(src_rate, dst_rate, channels))
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            EncoderResampler::Cubic(CubicResampler::new(src_rate, dst_rate, channels))
+        }
+    }
+
+    fn process(&mut self, samples: &[i16], channels: usize) -> Vec<i16> {
+        match self {
+            #[cfg(feature = "std")]
+            EncoderResampler::Sinc(resampler) => resampler.process(samples, channels),
+            EncoderResampler::Cubic(resampler) => resampler.process(samples, channels),
+        }
+    }
+
+    fn flush(&mut self, channels: usize) -> Vec<i16> {
+        match self {
+            #[cfg(feature = "std")]
+            EncoderResampler::Sinc(resampler) => resampler.flush(channels),
+            EncoderResampler::Cubic(resampler) => resampler.flush(channels),
+        }
+    }
+}
+
 pub enum SeaEncoderState {
     Start,
     WritingFrames,
     Finished,
 }
 
+/// Selects what `CbrEncoder` predicts each sample from before quantizing the residual.
+/// `Lms` is the codec's original fast adaptive predictor; `Lpc` fits a fixed-order linear
+/// predictor per chunk via Levinson-Durbin, which tends to compress tonal/low-noise sources
+/// better at the cost of the up-front coefficient search.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SeaPredictorKind {
+    Lms,
+    Lpc { order: u8 },
+}
+
+/// The PCM layout `SeaEncoder::read_samples` expects from its reader. The codec's internal
+/// pipeline is i16 end to end, so anything wider is downscaled and anything narrower (`U8`) is
+/// widened to i16 on ingest rather than changing the pipeline's own width; `seaconv`/`SeaWavEncoder`
+/// pick this from the source WAV's `fmt ` tag and bit depth so callers aren't forced to
+/// pre-convert everything to 16-bit PCM by hand.
+///
+/// This is a deliberately smaller scope than "carry 24/32-bit precision end to end": that would
+/// need `SeaLMS` (in `codec::lms`) and the quantizer/dequantizer tables (`codec::qt`/`codec::dqt`)
+/// widened from i16 to i32 throughout prediction and quantization, not just at the ingest/output
+/// boundary `to_i16`/`from_i16` sit at. `codec::lms` and `codec::qt` aren't implemented in this
+/// tree, so that widening isn't attempted here - a 24-bit or float source fed to `SeaEncoder`
+/// still gets truncated to i16 precision on its way into the predictor, the same as before this
+/// type existed. What this type *does* fix is the output side: a caller that knows its source
+/// was wider than 16-bit can get that container depth back (still only carrying the 16 bits the
+/// pipeline actually kept) instead of always reading out 16-bit PCM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SeaSampleFormat {
+    // unsigned, 8-bit, centered on 128 (the WAV convention for 8-bit PCM)
+    U8,
+    #[default]
+    I16,
+    // signed little-endian, 3 bytes per sample
+    I24,
+    I32,
+    // IEEE 754, little-endian, nominally in [-1.0, 1.0]
+    F32,
+}
+
+impl SeaSampleFormat {
+    pub fn bytes_per_sample(self) -> usize {
+        match self {
+            SeaSampleFormat::U8 => 1,
+            SeaSampleFormat::I16 => 2,
+            SeaSampleFormat::I24 => 3,
+            SeaSampleFormat::I32 => 4,
+            SeaSampleFormat::F32 => 4,
+        }
+    }
+
+    /// Downscales one sample's raw bytes (`bytes_per_sample()` long, little-endian) to the
+    /// codec's internal i16 domain.
+    fn to_i16(self, bytes: &[u8]) -> i16 {
+        match self {
+            // centered on 128, so the offset has to come off before widening to the signed domain
+            SeaSampleFormat::U8 => ((bytes[0] as i16) - 128) << 8,
+            SeaSampleFormat::I16 => i16::from_le_bytes([bytes[0], bytes[1]]),
+            // keep the top 16 bits of the 24-bit sample, same as truncating a 24-bit DAC value
+            // down to 16-bit precision
+            SeaSampleFormat::I24 => {
+                let sample = i32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0])
+                    .wrapping_shl(8)
+                    .wrapping_shr(8);
+                (sample >> 8) as i16
+            }
+            // same idea as I24: keep the top 16 bits
+            SeaSampleFormat::I32 => {
+                let sample = i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                (sample >> 16) as i16
+            }
+            SeaSampleFormat::F32 => {
+                let sample = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                clamp_i16((sample.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i32)
+            }
+        }
+    }
+
+    /// This format's `(format_tag, bits_per_sample)` pair for a WAV `fmt ` chunk, the inverse of
+    /// `sample_format_from_wav` in `wav.rs`.
+    pub fn wav_format_tag(self) -> (u16, u16) {
+        match self {
+            SeaSampleFormat::U8 => (1, 8),
+            SeaSampleFormat::I16 => (1, 16),
+            SeaSampleFormat::I24 => (1, 24),
+            SeaSampleFormat::I32 => (1, 32),
+            SeaSampleFormat::F32 => (3, 32),
+        }
+    }
+
+    /// Widens a decoded i16 sample back out to this format's raw little-endian bytes
+    /// (`bytes_per_sample()` long), the inverse of `to_i16`. The codec's internal pipeline only
+    /// ever carries i16 precision end to end - `SeaLMS`'s adaptive weights and the quantizer's
+    /// dequant tables aren't wide enough to hold more - so this doesn't recover anything lost at
+    /// `to_i16` ingestion; it just re-expresses the decoded value in the requested container
+    /// depth, e.g. so a 24-bit source round-trips back out to a 24-bit WAV file instead of always
+    /// 16-bit. Carrying real >16-bit precision through prediction and quantization themselves
+    /// would need `SeaLMS`/the dequant tables widened to i32, which is a larger change than this
+    /// ingest/output boundary.
+    pub fn from_i16(self, sample: i16, out: &mut [u8]) {
+        match self {
+            SeaSampleFormat::U8 => out[0] = ((sample >> 8) as i32 + 128) as u8,
+            SeaSampleFormat::I16 => out.copy_from_slice(&sample.to_le_bytes()),
+            SeaSampleFormat::I24 => {
+                let widened = clamp_sample((sample as i32) << 8, 24);
+                out.copy_from_slice(&widened.to_le_bytes()[..3]);
+            }
+            SeaSampleFormat::I32 => {
+                let widened = (sample as i32) << 16;
+                out.copy_from_slice(&widened.to_le_bytes());
+            }
+            SeaSampleFormat::F32 => {
+                let normalized = sample as f32 / i16::MAX as f32;
+                out.copy_from_slice(&normalized.to_le_bytes());
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct EncoderSettings {
     pub scale_factor_bits: u8,
@@ -20,6 +194,41 @@ pub struct EncoderSettings {
     pub residual_bits: f32, // 1-8
     pub frames_per_chunk: u16,
     pub vbr: bool,
+    // Golomb-Rice code the quantized residuals instead of packing them at a fixed width.
+    // Ignored when `vbr` is set, since VBR already varies the per-sample width itself.
+    pub rice: bool,
+    // for 2-channel input, additionally try mid/side decorrelation per chunk and keep
+    // whichever of left/right or mid/side ranks lower
+    pub joint_stereo: bool,
+    pub predictor: SeaPredictorKind,
+    // the reader's PCM layout; samples are downscaled to i16 on ingest when this isn't `I16`
+    pub input_format: SeaSampleFormat,
+    // remixes each frame read from the reader before it's handed to the codec, e.g. to encode a
+    // downmix of true surround source material; `channels` passed to `SeaEncoder::new` must then
+    // be the mixer's `output_channels()`, while the reader itself still supplies
+    // `input_channels()` per frame. `None` passes frames through unchanged.
+    pub channel_mixer: Option<ChannelMixer>,
+    // retargets the input to `(target_rate, mode)` before encoding. Only honored by `sea_encode`,
+    // which has the whole input buffer up front and resamples it in one pass; `SeaEncoder` itself
+    // ignores it (`input_sample_rate` below is its streaming counterpart). `None` encodes at the
+    // rate passed to `sea_encode`/`SeaEncoder::new` unchanged.
+    pub resample: Option<(u32, InterpolationMode)>,
+    // the reader's actual sample rate, when it differs from the `sample_rate` passed to
+    // `SeaEncoder::new`. Unlike `resample`, this *is* honored by the streaming `encode_frame`:
+    // resampling happens on the input side, inside `read_samples`, before chunk framing is ever
+    // decided, so however many native-rate frames it takes to fill one output chunk is entirely
+    // an input-reading concern and doesn't disturb the fixed chunk-size invariant. `None` assumes
+    // the reader is already at `sample_rate`.
+    pub input_sample_rate: Option<u32>,
+    // when `vbr` is set, steers the long-run average bitrate toward this many kbps: after each
+    // chunk, `SeaEncoder` compares actual bits emitted so far against the ideal cumulative total
+    // for a stream running at this rate, nudges `current_residual_bits` up or down for the next
+    // chunk, and writes the result into `self.file.settings.residual_bits` so it actually takes
+    // effect (see `SeaEncoder::current_residual_bits`; `VbrEncoder::adjust_target_bitrate` is the
+    // same correction for a caller driving its own persistent `VbrEncoder` directly). `None`
+    // leaves `residual_bits` fixed at its starting value, as before. Ignored when `vbr` is unset,
+    // since CBR's residual width is already exact rather than a target to converge on.
+    pub target_bitrate: Option<u32>,
 }
 
 impl Default for EncoderSettings {
@@ -30,22 +239,72 @@ impl Default for EncoderSettings {
             scale_factor_frames: 20,
             residual_bits: 3.0,
             vbr: false,
+            rice: false,
+            joint_stereo: false,
+            predictor: SeaPredictorKind::Lms,
+            input_format: SeaSampleFormat::I16,
+            channel_mixer: None,
+            resample: None,
+            input_sample_rate: None,
+            target_bitrate: None,
         }
     }
 }
 
+/// One chunk's starting frame and absolute byte offset, recorded by `SeaEncoder::with_seek_index`
+/// and written out as a trailing index block by `finalize` - lets a future decoder jump straight
+/// to the chunk containing a requested frame instead of decoding from the start, the same role
+/// MP4's sample table plays. Mirrors `decoder::SeekIndexEntry`'s shape, but that one is rebuilt in
+/// memory as a stream is decoded; this one is the persisted, on-disk form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkIndexEntry {
+    pub frame: u32,
+    pub byte_offset: u64,
+}
+
+const SEEK_INDEX_MAGIC: [u8; 4] = *b"SIDX";
+// written as the last 12 bytes of the file: the index block's starting byte offset (u64 LE)
+// followed by this magic, so a reader can find the index by seeking to `len - 12` without
+// needing anything recorded earlier in the stream
+const SEEK_INDEX_TRAILER_MAGIC: [u8; 4] = *b"SIDE";
+
 pub struct SeaEncoder<R, W> {
     reader: R,
     writer: W,
     file: SeaFile,
     state: SeaEncoderState,
     written_frames: u32,
+    input_format: SeaSampleFormat,
+    channel_mixer: Option<ChannelMixer>,
+    input_resampler: Option<EncoderResampler>,
+    // resampled, not-yet-consumed native-rate input, interleaved at the mixer's input channel
+    // width (pre-mix); only used when `input_resampler` is set, see `read_resampled_samples`
+    resample_carry: Vec<i16>,
+    // set once `input_resampler` has been flushed at end of input, so a later call doesn't flush
+    // (and so emit spurious trailing silence) a second time
+    resample_flushed: bool,
+    // total bytes written so far (header + chunks), tracked by hand since every write above goes
+    // through this same writer sequentially; used to stamp each chunk's real offset into
+    // `chunk_index` without needing the writer to support seeking. `None` until
+    // `with_seek_index` turns indexing on.
+    chunk_index: Option<Vec<ChunkIndexEntry>>,
+    bytes_written: u64,
+    // whether `EncoderSettings::vbr` was set; gates the `target_bitrate` feedback step, since CBR
+    // has no residual width left to adjust
+    vbr: bool,
+    // kbps `current_residual_bits` is steered toward, see `EncoderSettings::target_bitrate`
+    target_bitrate: Option<u32>,
+    // the residual width the *next* chunk should be encoded at, adjusted after every chunk when
+    // `target_bitrate` is set; starts at `EncoderSettings::residual_bits` and otherwise never
+    // changes. Written back into `self.file.settings.residual_bits` as soon as it changes, so
+    // `make_chunk` actually picks it up for the following chunk - see `encode_frame`.
+    current_residual_bits: f32,
 }
 
 impl<R, W> SeaEncoder<R, W>
 where
-    R: io::Read,
-    W: io::Write,
+    R: Read,
+    W: Write,
 {
     pub fn new(
         channels: u8,
@@ -55,6 +314,15 @@ where
         reader: R,
         mut writer: W,
     ) -> Result<Self, SeaError> {
+        // `SeaPredictorKind::Lpc { order }` is a public value a caller can set directly, not an
+        // internal invariant - reject it here instead of letting it reach `SeaLpc::new`'s
+        // `assert!(order > 0 && order <= SEA_LPC_MAX_ORDER)` and panic the encoder
+        if let SeaPredictorKind::Lpc { order } = settings.predictor {
+            if order == 0 || order as usize > SEA_LPC_MAX_ORDER {
+                return Err(SeaError::InvalidParameters);
+            }
+        }
+
         let header = SeaFileHeader {
             version: 1,
             channels,
@@ -62,7 +330,7 @@ where
             frames_per_chunk: settings.frames_per_chunk,
             sample_rate,
             total_frames: total_frames.unwrap_or(0),
-            metadata: Rc::new(String::new()),
+            metadata: Rc::new(Default::default()),
         };
 
         let file = SeaFile::new(header, &settings)?;
@@ -76,31 +344,189 @@ where
             }
         }
 
+        let input_channels = settings
+            .channel_mixer
+            .as_ref()
+            .map_or(channels as usize, |mixer| mixer.input_channels());
+
+        let input_resampler = match settings.input_sample_rate {
+            Some(input_rate) if input_rate != sample_rate => {
+                Some(EncoderResampler::new(input_rate, sample_rate, input_channels))
+            }
+            _ => None,
+        };
+
+        // the header is already on the writer when `total_frames` is `Some(0)` (see above)
+        let header_bytes_written = match state {
+            SeaEncoderState::WritingFrames => file.header.serialize().len() as u64,
+            _ => 0,
+        };
+
         Ok(SeaEncoder {
             file,
             state,
             reader,
             writer,
             written_frames: 0,
+            input_format: settings.input_format,
+            channel_mixer: settings.channel_mixer,
+            input_resampler,
+            resample_carry: Vec::new(),
+            resample_flushed: false,
+            chunk_index: None,
+            bytes_written: header_bytes_written,
+            vbr: settings.vbr,
+            target_bitrate: settings.target_bitrate,
+            current_residual_bits: settings.residual_bits,
         })
     }
 
-    fn read_samples(&mut self, max_sample_count: usize) -> Result<Vec<i16>, SeaError> {
-        let buffer_size = max_sample_count * std::mem::size_of::<i16>();
+    /// The residual bit width the next chunk will target, adjusted by `target_bitrate`'s feedback
+    /// loop after every chunk when VBR rate control is enabled; fixed at the starting
+    /// `residual_bits` otherwise. Every time this changes, `encode_frame` writes it straight into
+    /// `self.file.settings.residual_bits`, so this getter reports the width the *next*
+    /// `make_chunk` call will actually encode at - exposed so a caller can log the rate curve over
+    /// a file, e.g. to plot how closely a stream is tracking its target bitrate.
+    pub fn current_residual_bits(&self) -> f32 {
+        self.current_residual_bits
+    }
+
+    /// Turns on recording each chunk's starting frame and byte offset as it's written, so
+    /// `finalize` appends a seek index block (see `ChunkIndexEntry`) after the last chunk. Unlike
+    /// the request that inspired this, the index isn't signaled by a header flag -
+    /// `SeaFileHeader`'s binary layout lives in `codec::file`, which this tree doesn't actually
+    /// have an implementation for, so there's no header field available to patch. Instead the
+    /// index is self-describing: its own trailer (see `SEEK_INDEX_TRAILER_MAGIC`) is always the
+    /// last 12 bytes of the file when present, discoverable by seeking from EOF, the same way a
+    /// zip's end-of-central-directory record is found without anything earlier in the file
+    /// announcing it.
+    pub fn with_seek_index(mut self) -> Self {
+        self.chunk_index = Some(Vec::new());
+        self
+    }
+
+    /// Embeds `tags` (e.g. `title`/`artist`/the tool that produced the file) into the header's
+    /// `metadata` field, the way a container format carries track metadata - `SeaFileHeader.metadata`
+    /// is otherwise always initialized empty by `new`. Entries are serialized as `key=value` lines
+    /// so a decoder can split on `\n` and `=` without needing a length-prefixed format of its own.
+    ///
+    /// The header is written out as soon as the first chunk is encoded (or immediately, when
+    /// `total_frames` is `0`; see `new`), so this returns `SeaError::EncoderClosed` once `state`
+    /// has left `Start` - tags set after that point would silently not make it into the file.
+    pub fn set_tags(&mut self, tags: &BTreeMap<String, String>) -> Result<(), SeaError> {
+        if !matches!(self.state, SeaEncoderState::Start) {
+            return Err(SeaError::EncoderClosed);
+        }
+
+        self.file.header.metadata = Rc::new(
+            tags.iter()
+                .map(|(key, value)| format!("{key}={value}"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+
+        Ok(())
+    }
+
+    /// Builder form of `set_tags`, for constructing a `SeaEncoder` with tags already attached.
+    pub fn with_tags(mut self, tags: &BTreeMap<String, String>) -> Result<Self, SeaError> {
+        self.set_tags(tags)?;
+        Ok(self)
+    }
+
+    /// Reads up to `max_frame_count` native-input-format frames from the reader and converts them
+    /// to the codec's internal i16 domain, at the reader's own channel count (pre-mix). Returns
+    /// fewer than `max_frame_count` frames' worth only once the reader is exhausted.
+    fn read_native_samples(
+        &mut self,
+        max_frame_count: usize,
+        input_channels: usize,
+    ) -> Result<Vec<i16>, SeaError> {
+        let bytes_per_sample = self.input_format.bytes_per_sample();
+        let buffer_size = max_frame_count * input_channels * bytes_per_sample;
         let buffer = read_max_or_zero(&mut self.reader, buffer_size)?;
 
         if buffer.is_empty() {
             return Ok(Vec::new());
         }
 
-        if buffer.len() % (std::mem::size_of::<i16>() * self.file.header.channels as usize) != 0 {
-            return Err(SeaError::IoError(io::Error::from(
-                io::ErrorKind::UnexpectedEof,
-            )));
+        if buffer.len() % (bytes_per_sample * input_channels) != 0 {
+            return Err(SeaError::ReadError);
         }
 
-        let samples: &[i16] = cast_slice(&buffer);
-        Ok(samples.to_vec())
+        Ok(if matches!(self.input_format, SeaSampleFormat::I16) {
+            let samples: &[i16] = cast_slice(&buffer);
+            samples.to_vec()
+        } else {
+            buffer
+                .chunks_exact(bytes_per_sample)
+                .map(|sample| self.input_format.to_i16(sample))
+                .collect()
+        })
+    }
+
+    /// Resamples native-rate input down to `file.header.sample_rate` until `resample_carry` holds
+    /// at least `max_frame_count` output frames (or the reader runs dry), then hands back exactly
+    /// that many - carrying any excess over to the next call so every chunk still gets the exact
+    /// frame count `encode_frame` expects, regardless of how the input/output rates divide.
+    fn read_resampled_samples(
+        &mut self,
+        max_frame_count: usize,
+        input_channels: usize,
+    ) -> Result<Vec<i16>, SeaError> {
+        let needed = max_frame_count * input_channels;
+
+        while self.resample_carry.len() < needed {
+            let native = self.read_native_samples(max_frame_count, input_channels)?;
+
+            if native.is_empty() {
+                if !self.resample_flushed {
+                    self.resample_flushed = true;
+                    let tail = self
+                        .input_resampler
+                        .as_mut()
+                        .unwrap()
+                        .flush(input_channels);
+                    self.resample_carry.extend(tail);
+                }
+                break;
+            }
+
+            let resampled = self
+                .input_resampler
+                .as_mut()
+                .unwrap()
+                .process(&native, input_channels);
+            self.resample_carry.extend(resampled);
+        }
+
+        let take = needed.min(self.resample_carry.len());
+        let take = take - (take % input_channels);
+        Ok(self.resample_carry.drain(..take).collect())
+    }
+
+    /// Reads up to `max_frame_count` frames from the reader and converts them to the codec's
+    /// internal i16 domain, resampling from `EncoderSettings::input_sample_rate` first when
+    /// configured, then remixing from `channel_mixer`'s input layout to `file.header`'s (output)
+    /// channel count when a mixer is configured.
+    fn read_samples(&mut self, max_frame_count: usize) -> Result<Vec<i16>, SeaError> {
+        let input_channels = self
+            .channel_mixer
+            .as_ref()
+            .map_or(self.file.header.channels as usize, |mixer| {
+                mixer.input_channels()
+            });
+
+        let samples = if self.input_resampler.is_some() {
+            self.read_resampled_samples(max_frame_count, input_channels)?
+        } else {
+            self.read_native_samples(max_frame_count, input_channels)?
+        };
+
+        Ok(match &self.channel_mixer {
+            Some(mixer) => mixer.process(&samples),
+            None => samples,
+        })
     }
 
     pub fn encode_frame(&mut self) -> Result<bool, SeaError> {
@@ -108,7 +534,6 @@ where
             return Err(SeaError::EncoderClosed);
         }
 
-        let channels = self.file.header.channels;
         let frames = if self.file.header.total_frames > 0 {
             (self.file.header.frames_per_chunk as usize)
                 .min(self.file.header.total_frames as usize - self.written_frames as usize)
@@ -118,8 +543,7 @@ where
 
         let full_size_samples =
             self.file.header.frames_per_chunk as usize * self.file.header.channels as usize;
-        let samples_to_read = frames * channels as usize;
-        let samples: Vec<i16> = self.read_samples(samples_to_read)?;
+        let samples: Vec<i16> = self.read_samples(frames)?;
         let eof: bool = samples.is_empty() || samples.len() < full_size_samples;
 
         if !samples.is_empty() {
@@ -133,12 +557,42 @@ where
 
             // we need to write file header after the first chunk is generated
             if matches!(self.state, SeaEncoderState::Start) {
-                self.writer.write_all(&self.file.header.serialize())?;
+                let header_bytes = self.file.header.serialize();
+                self.writer.write_all(&header_bytes)?;
+                self.bytes_written += header_bytes.len() as u64;
                 self.state = SeaEncoderState::WritingFrames;
             }
 
+            if let Some(chunk_index) = &mut self.chunk_index {
+                chunk_index.push(ChunkIndexEntry {
+                    frame: self.written_frames,
+                    byte_offset: self.bytes_written,
+                });
+            }
+
             self.writer.write_all(&encoded_chunk)?;
+            self.bytes_written += encoded_chunk.len() as u64;
             self.written_frames += frames as u32;
+
+            // nudge the residual width towards the configured average bitrate using how far
+            // off we are so far, rather than deciding a single static width up front like
+            // `VbrEncoder::abr_settings` does, then feed the corrected width back into
+            // `self.file.settings` so the *next* `make_chunk` call actually encodes at it -
+            // otherwise this would just be a number we compute and discard
+            if self.vbr {
+                if let Some(target_kbps) = self.target_bitrate {
+                    let ideal_bits = target_kbps as f32
+                        * 1000.0
+                        * self.written_frames as f32
+                        / self.file.header.sample_rate as f32;
+                    let bits_emitted = (self.bytes_written * 8) as f32;
+                    let deficit = ideal_bits - bits_emitted;
+                    let correction = (deficit / ideal_bits.max(1.0)).clamp(-0.5, 0.5);
+                    self.current_residual_bits =
+                        (self.current_residual_bits + correction).clamp(1.0, 8.0);
+                    self.file.settings.residual_bits = self.current_residual_bits;
+                }
+            }
         }
 
         if eof {
@@ -153,6 +607,20 @@ where
     }
 
     pub fn finalize(&mut self) -> Result<(), SeaError> {
+        if let Some(chunk_index) = self.chunk_index.take() {
+            let index_start = self.bytes_written;
+
+            self.writer.write_all(&SEEK_INDEX_MAGIC)?;
+            self.writer.write_all(&(chunk_index.len() as u32).to_le_bytes())?;
+            for entry in &chunk_index {
+                self.writer.write_all(&entry.frame.to_le_bytes())?;
+                self.writer.write_all(&entry.byte_offset.to_le_bytes())?;
+            }
+
+            self.writer.write_all(&index_start.to_le_bytes())?;
+            self.writer.write_all(&SEEK_INDEX_TRAILER_MAGIC)?;
+        }
+
         self.writer.flush()?;
         self.state = SeaEncoderState::Finished;
         Ok(())
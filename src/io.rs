@@ -0,0 +1,51 @@
+//! Minimal `Read`/`Write` traits so the encoder/decoder can run on `no_std` + `alloc` targets,
+//! the same split zstd-rs uses between its `std` and `no_std` builds. With the default `std`
+//! feature enabled these are blanket-implemented for every `std::io::Read`/`Write` type, so
+//! existing callers passing files, `Vec<u8>`, `Cursor`, etc. are unaffected; a `no_std` build
+//! implements them directly against whatever transport it has (a flash buffer, a ring buffer).
+//!
+//! `SeaDecoder::seek_to_frame` still requires `std::io::Seek` - `no_std` targets don't get a
+//! substitute for it yet, so seeking is only available with the `std` feature enabled.
+
+use super::codec::common::SeaError;
+
+pub trait Read {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, SeaError>;
+
+    fn read_exact(&mut self, mut buf: &mut [u8]) -> Result<(), SeaError> {
+        while !buf.is_empty() {
+            match self.read(buf)? {
+                0 => return Err(SeaError::ReadError),
+                n => buf = &mut buf[n..],
+            }
+        }
+        Ok(())
+    }
+}
+
+pub trait Write {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), SeaError>;
+    fn flush(&mut self) -> Result<(), SeaError>;
+}
+
+#[cfg(feature = "std")]
+impl<T: std::io::Read> Read for T {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, SeaError> {
+        std::io::Read::read(self, buf).map_err(SeaError::from)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), SeaError> {
+        std::io::Read::read_exact(self, buf).map_err(SeaError::from)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: std::io::Write> Write for T {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), SeaError> {
+        std::io::Write::write_all(self, buf).map_err(SeaError::from)
+    }
+
+    fn flush(&mut self) -> Result<(), SeaError> {
+        std::io::Write::flush(self).map_err(SeaError::from)
+    }
+}
@@ -1,20 +1,57 @@
+//! `std` is on by default and covers everything below. Disabling it and enabling `no_std`
+//! drops `SeaEncoder`/`SeaDecoder`'s `std::io::Read`/`Write` bound in favor of the minimal
+//! `io::Read`/`Write` traits, so they run against `core` + `alloc` transports; `wasm_api` and
+//! `wav` are thin `std::io` wrappers and stay `std`-only, as does `SeaDecoder::seek_to_frame`
+//! and the windowed-sinc `set_resample` (see `io.rs` and `decoder.rs`); `with_resample`'s cubic
+//! resampler works either way. The quantization and LPC/Rice-parameter search still call a
+//! handful of `f32`/`f64` methods (`round`, `floor`, `log2`) that `core` doesn't provide
+//! standalone - a `no_std` target needs those satisfied by a `libm`-backed shim, which is a
+//! follow-up, not part of this change.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+#[cfg(feature = "std")]
 use std::io::Cursor;
 
+#[cfg(feature = "std")]
 use bytemuck::cast_slice;
+#[cfg(feature = "std")]
 use codec::{
     decoder::SeaDecoder,
     encoder::{EncoderSettings, SeaEncoder},
 };
 
 pub mod codec;
+pub mod io;
+#[cfg(feature = "std")]
 pub mod wasm_api;
+#[cfg(feature = "std")]
+pub mod wav;
 
+#[cfg(feature = "std")]
 pub fn sea_encode(
     input_samples: &[i16],
     sample_rate: u32,
     channels: u32,
     settings: EncoderSettings,
 ) -> Vec<u8> {
+    use codec::resample::BufferResampler;
+
+    // `SeaEncoder` doesn't act on `settings.resample` itself, so it's applied here, over the
+    // whole buffer, before any chunking happens
+    let resampled_samples;
+    let (sample_rate, input_samples) = match settings.resample {
+        Some((target_rate, mode)) if target_rate != sample_rate => {
+            let mut resampler =
+                BufferResampler::new(mode, sample_rate, target_rate, channels as usize);
+            resampled_samples = resampler.process(input_samples, channels as usize);
+            (target_rate, &resampled_samples[..])
+        }
+        _ => (sample_rate, input_samples),
+    };
+
     let u8_input_samples: &[u8] = cast_slice(input_samples);
     let mut cursor: Cursor<_> = Cursor::new(u8_input_samples);
     let mut sea_encoded = Vec::<u8>::with_capacity(input_samples.len());
@@ -34,12 +71,14 @@ pub fn sea_encode(
     sea_encoded
 }
 
+#[cfg(feature = "std")]
 pub struct SeaDecodeInfo {
     pub samples: Vec<i16>,
     pub sample_rate: u32,
     pub channels: u32,
 }
 
+#[cfg(feature = "std")]
 pub fn sea_decode(encoded: &[u8]) -> SeaDecodeInfo {
     let mut cursor: Cursor<&[u8]> = Cursor::new(encoded);
     let mut sea_decoded = Vec::<u8>::with_capacity(encoded.len() * 8);
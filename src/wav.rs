@@ -0,0 +1,199 @@
+use std::io;
+
+use crate::{
+    codec::common::{read_u16_le, read_u32_be, read_u32_le, SeaError},
+    decoder::SeaDecoder,
+    encoder::{EncoderSettings, SeaEncoder, SeaSampleFormat},
+};
+
+const RIFF_MAGIC: u32 = u32::from_be_bytes(*b"RIFF");
+const WAVE_MAGIC: u32 = u32::from_be_bytes(*b"WAVE");
+const FMT_MAGIC: u32 = u32::from_be_bytes(*b"fmt ");
+const DATA_MAGIC: u32 = u32::from_be_bytes(*b"data");
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+
+/// Maps a `fmt ` chunk's format tag + bit depth to the `SeaSampleFormat` `SeaEncoder` should
+/// downscale from, or `None` for a layout this reader doesn't understand.
+fn sample_format_from_wav(format_tag: u16, bits_per_sample: u16) -> Option<SeaSampleFormat> {
+    match (format_tag, bits_per_sample) {
+        (WAVE_FORMAT_PCM, 8) => Some(SeaSampleFormat::U8),
+        (WAVE_FORMAT_PCM, 16) => Some(SeaSampleFormat::I16),
+        (WAVE_FORMAT_PCM, 24) => Some(SeaSampleFormat::I24),
+        (WAVE_FORMAT_PCM, 32) => Some(SeaSampleFormat::I32),
+        (WAVE_FORMAT_IEEE_FLOAT, 32) => Some(SeaSampleFormat::F32),
+        _ => None,
+    }
+}
+
+fn skip_bytes<R: io::Read>(reader: &mut R, count: usize) -> io::Result<()> {
+    let mut remaining = count;
+    let mut buf = [0u8; 256];
+    while remaining > 0 {
+        let to_read = remaining.min(buf.len());
+        reader.read_exact(&mut buf[..to_read])?;
+        remaining -= to_read;
+    }
+    Ok(())
+}
+
+/// Wraps `SeaEncoder` to accept a canonical `.wav` file directly: it parses the `fmt ` and
+/// `data` chunks (skipping anything else, like `LIST`/`fact`) so callers don't need to splice
+/// out the PCM and sample rate/channel count by hand first.
+pub struct SeaWavEncoder<R, W> {
+    inner: SeaEncoder<R, W>,
+}
+
+impl<R, W> SeaWavEncoder<R, W>
+where
+    R: io::Read,
+    W: io::Write,
+{
+    pub fn new(mut reader: R, writer: W, settings: EncoderSettings) -> Result<Self, SeaError> {
+        if read_u32_be(&mut reader)? != RIFF_MAGIC {
+            return Err(SeaError::InvalidFile);
+        }
+        let _riff_size = read_u32_le(&mut reader)?;
+        if read_u32_be(&mut reader)? != WAVE_MAGIC {
+            return Err(SeaError::InvalidFile);
+        }
+
+        let mut channels: Option<u16> = None;
+        let mut sample_rate: Option<u32> = None;
+        let mut data_size: Option<u32> = None;
+        let mut input_format: Option<SeaSampleFormat> = None;
+
+        loop {
+            let chunk_id = read_u32_be(&mut reader)?;
+            let chunk_size = read_u32_le(&mut reader)?;
+
+            if chunk_id == FMT_MAGIC {
+                let format_tag = read_u16_le(&mut reader)?;
+                let chunk_channels = read_u16_le(&mut reader)?;
+                let chunk_sample_rate = read_u32_le(&mut reader)?;
+                let _byte_rate = read_u32_le(&mut reader)?;
+                let _block_align = read_u16_le(&mut reader)?;
+                let bits_per_sample = read_u16_le(&mut reader)?;
+
+                // skip any format extension bytes beyond the 16-byte PCM fmt chunk we just read
+                skip_bytes(&mut reader, chunk_size.saturating_sub(16) as usize)?;
+
+                input_format = Some(
+                    sample_format_from_wav(format_tag, bits_per_sample)
+                        .ok_or(SeaError::InvalidFile)?,
+                );
+                channels = Some(chunk_channels);
+                sample_rate = Some(chunk_sample_rate);
+            } else if chunk_id == DATA_MAGIC {
+                data_size = Some(chunk_size);
+                break;
+            } else {
+                skip_bytes(&mut reader, chunk_size as usize)?;
+            }
+
+            // chunks are word-aligned: an odd-sized chunk is followed by a pad byte
+            if chunk_size % 2 == 1 {
+                skip_bytes(&mut reader, 1)?;
+            }
+        }
+
+        let channels = channels.ok_or(SeaError::InvalidFile)?;
+        let sample_rate = sample_rate.ok_or(SeaError::InvalidFile)?;
+        let data_size = data_size.ok_or(SeaError::InvalidFile)?;
+        let input_format = input_format.ok_or(SeaError::InvalidFile)?;
+
+        let total_frames =
+            data_size / (input_format.bytes_per_sample() as u32 * channels as u32);
+
+        let inner = SeaEncoder::new(
+            channels as u8,
+            sample_rate,
+            Some(total_frames),
+            EncoderSettings {
+                input_format,
+                ..settings
+            },
+            reader,
+            writer,
+        )?;
+
+        Ok(Self { inner })
+    }
+
+    pub fn encode_frame(&mut self) -> Result<bool, SeaError> {
+        self.inner.encode_frame()
+    }
+
+    pub fn finalize(&mut self) -> Result<(), SeaError> {
+        self.inner.finalize()
+    }
+}
+
+/// Wraps `SeaDecoder` to emit a canonical `.wav` file directly: a 44-byte `RIFF`/`fmt `/`data`
+/// header is written up front from the `.sea` file's own header, ahead of the decoded PCM.
+pub struct SeaWavDecoder<R, W> {
+    inner: SeaDecoder<R, W>,
+}
+
+impl<R, W> SeaWavDecoder<R, W>
+where
+    R: io::Read,
+    W: io::Write,
+{
+    /// Emits 16-bit PCM, regardless of the bit depth the source was originally encoded from -
+    /// the `.sea` header has nowhere to record that depth, so a caller that cares about getting
+    /// the original depth back out (e.g. for a 24-bit master) needs `with_output_format` and its
+    /// own memory of what it encoded at.
+    pub fn new(reader: R, writer: W) -> Result<Self, SeaError> {
+        Self::with_output_format(reader, writer, SeaSampleFormat::I16)
+    }
+
+    /// Like `new`, but writes the `fmt `/`data` chunks and every decoded sample at
+    /// `output_format`'s depth instead of always 16-bit, via `SeaSampleFormat::from_i16`.
+    pub fn with_output_format(
+        reader: R,
+        writer: W,
+        output_format: SeaSampleFormat,
+    ) -> Result<Self, SeaError> {
+        let mut inner = SeaDecoder::new(reader, writer)?.with_output_format(output_format);
+        let header = inner.get_header();
+
+        let (format_tag, bits_per_sample) = output_format.wav_format_tag();
+        let bytes_per_sample = output_format.bytes_per_sample() as u32;
+        let byte_rate = header.sample_rate * header.channels as u32 * bytes_per_sample;
+        let block_align = header.channels as u16 * bytes_per_sample as u16;
+        let data_size = header.total_frames * header.channels as u32 * bytes_per_sample;
+        let riff_size = 36 + data_size;
+
+        let mut wav_header = Vec::with_capacity(44);
+        wav_header.extend_from_slice(b"RIFF");
+        wav_header.extend_from_slice(&riff_size.to_le_bytes());
+        wav_header.extend_from_slice(b"WAVE");
+        wav_header.extend_from_slice(b"fmt ");
+        wav_header.extend_from_slice(&16u32.to_le_bytes());
+        wav_header.extend_from_slice(&format_tag.to_le_bytes());
+        wav_header.extend_from_slice(&(header.channels as u16).to_le_bytes());
+        wav_header.extend_from_slice(&header.sample_rate.to_le_bytes());
+        wav_header.extend_from_slice(&byte_rate.to_le_bytes());
+        wav_header.extend_from_slice(&block_align.to_le_bytes());
+        wav_header.extend_from_slice(&bits_per_sample.to_le_bytes());
+        wav_header.extend_from_slice(b"data");
+        wav_header.extend_from_slice(&data_size.to_le_bytes());
+
+        inner.writer_mut().write_all(&wav_header)?;
+
+        Ok(Self { inner })
+    }
+
+    pub fn decode_frame(&mut self) -> Result<bool, SeaError> {
+        self.inner.decode_frame()
+    }
+
+    pub fn finalize(&mut self) -> Result<(), SeaError> {
+        self.inner.finalize()
+    }
+
+    pub fn get_header(&self) -> crate::codec::file::SeaFileHeader {
+        self.inner.get_header()
+    }
+}
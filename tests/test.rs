@@ -1,5 +1,5 @@
 use helpers::{encode_decode, gen_test_signal, TEST_SAMPLE_RATE};
-use sea_codec::encoder::EncoderSettings;
+use sea_codec::encoder::{EncoderSettings, SeaPredictorKind};
 
 extern crate sea_codec;
 
@@ -62,3 +62,97 @@ fn test_parameters() {
         }
     }
 }
+
+// regression test for a mid/side lifting bug: `l - r` can need a 17th bit (e.g. l = i16::MAX,
+// r = i16::MIN), and clamping it instead of refusing the transform corrupted the decoded
+// left/right channels for chunks containing such pairs. Every 7th frame is an extreme pair and the
+// rest are half-scale pairs whose `l - r` still fits in 16 bits, so every chunk hits the overflow
+// case.
+#[test]
+fn test_joint_stereo_extreme_amplitude() {
+    let frame_count = TEST_SAMPLE_RATE as usize;
+    let mut input = Vec::with_capacity(frame_count * 2);
+    for i in 0..frame_count {
+        if i % 7 == 0 {
+            input.push(i16::MAX);
+            input.push(i16::MIN);
+        } else {
+            input.push(i16::MAX / 2);
+            input.push(i16::MIN / 2);
+        }
+    }
+
+    let output = encode_decode(
+        &input,
+        TEST_SAMPLE_RATE,
+        2,
+        EncoderSettings {
+            residual_bits: 8.0,
+            scale_factor_bits: 5,
+            joint_stereo: true,
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(input.len(), output.decoded.len());
+    let quality = helpers::get_audio_quality(&input, &output.decoded);
+    println!("Quality: {:?}", quality);
+    assert!(quality.psnr < -20.0);
+}
+
+// same regression as `test_joint_stereo_extreme_amplitude`, but over `VbrEncoder`'s path now that
+// it also tries mid/side decorrelation.
+#[test]
+fn test_joint_stereo_extreme_amplitude_vbr() {
+    let frame_count = TEST_SAMPLE_RATE as usize;
+    let mut input = Vec::with_capacity(frame_count * 2);
+    for i in 0..frame_count {
+        if i % 7 == 0 {
+            input.push(i16::MAX);
+            input.push(i16::MIN);
+        } else {
+            input.push(i16::MAX / 2);
+            input.push(i16::MIN / 2);
+        }
+    }
+
+    let output = encode_decode(
+        &input,
+        TEST_SAMPLE_RATE,
+        2,
+        EncoderSettings {
+            vbr: true,
+            residual_bits: 8.0,
+            scale_factor_bits: 5,
+            joint_stereo: true,
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(input.len(), output.decoded.len());
+    let quality = helpers::get_audio_quality(&input, &output.decoded);
+    println!("Quality: {:?}", quality);
+    assert!(quality.psnr < -20.0);
+}
+
+#[test]
+fn test_lpc_predictor() {
+    for channels in [1, 2] {
+        let input = gen_test_signal(channels, TEST_SAMPLE_RATE as usize);
+        for order in [2, 4, 8] {
+            let output = encode_decode(
+                &input,
+                TEST_SAMPLE_RATE,
+                channels as u32,
+                EncoderSettings {
+                    predictor: SeaPredictorKind::Lpc { order },
+                    ..Default::default()
+                },
+            );
+            assert_eq!(input.len(), output.decoded.len());
+            let quality = helpers::get_audio_quality(&input, &output.decoded);
+            println!("channels={} order={} Quality: {:?}", channels, order, quality);
+            assert!(quality.psnr < -15.0);
+        }
+    }
+}
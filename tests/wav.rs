@@ -12,8 +12,10 @@ pub fn read_wav(path: &Path) -> Result<Wave, Box<dyn Error>> {
     let mut reader = WavReader::open(path)?;
     let spec = reader.spec();
 
-    if spec.channels > 2 {
-        return Err("More than 2 channels are not supported".into());
+    // SeaEncoder itself handles up to SEA_MAX_CHANNELS (32) channels; only refuse what the codec
+    // itself can't represent instead of hard-coding this reader to stereo/mono
+    if spec.channels > 32 {
+        return Err(format!("More than 32 channels are not supported, got {}", spec.channels).into());
     }
 
     let samples_iter: Box<dyn Iterator<Item = i16>> =